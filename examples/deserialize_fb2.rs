@@ -15,6 +15,19 @@ fn main() {
         .iter()
         .map(|binary| (binary.id.clone(), Uuid::new_v4()))
         .collect();
-    let book = Book::from_fb2(book, book_id, &binary_ids);
+    let mut book = Book::from_fb2(book, book_id, &binary_ids);
     println!("{}", book.short_title);
+
+    let size_before = serde_json::to_vec(&book).unwrap().len();
+    let report = book.normalize_spans();
+    let size_after = serde_json::to_vec(&book).unwrap().len();
+    println!(
+        "spans: {} -> {}, text bytes: {} -> {}, json bytes: {} -> {}",
+        report.spans_before,
+        report.spans_after,
+        report.text_bytes_before,
+        report.text_bytes_after,
+        size_before,
+        size_after
+    );
 }
@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::position::text_between;
+use crate::walk::{parts, PartBlocks};
+use crate::{is_false, Book, Position};
+
+/// Bookmarks, highlights and notes of a reader, stored separately from the book
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Annotations {
+    pub book_id: Uuid,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bookmarks: Vec<Bookmark>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub highlights: Vec<Highlight>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<UserNote>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+pub struct Bookmark {
+    pub id: Uuid,
+    pub position: Position,
+    pub created: DateTime<Utc>,
+    pub modified: DateTime<Utc>,
+    /// Deleted annotations are kept, so that the deletion reaches other devices when merging.
+    /// A later modification on another device restores the annotation, a simultaneous one does not.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub deleted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+pub struct Highlight {
+    pub id: Uuid,
+    pub start: Position,
+    pub end: Position,
+    /// Text between the positions, to find the highlight when the book changes
+    pub quote: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    pub created: DateTime<Utc>,
+    pub modified: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub deleted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+pub struct UserNote {
+    pub id: Uuid,
+    pub start: Position,
+    pub end: Position,
+    pub quote: String,
+    pub text: String,
+    pub created: DateTime<Utc>,
+    pub modified: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub deleted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Resolution {
+    pub id: Uuid,
+    pub status: Status,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Status {
+    /// The positions and the quote match the book
+    Resolved,
+    /// The quote was found elsewhere in the book
+    Relocated { start: Position, end: Position },
+    /// Neither the positions nor the quote match the book
+    Stale,
+}
+
+impl Annotations {
+    pub fn new(book_id: Uuid) -> Annotations {
+        Annotations {
+            book_id,
+            bookmarks: vec![],
+            highlights: vec![],
+            notes: vec![],
+        }
+    }
+
+    /// Checks the annotations that are not deleted against the book.
+    /// Everything is stale if the annotations are for another book.
+    pub fn resolve(&self, book: &Book) -> Vec<Resolution> {
+        let parts = parts(book);
+        let matches = book.id == self.book_id;
+        let mut resolutions = vec![];
+        for bookmark in self.bookmarks.iter().filter(|b| !b.deleted) {
+            let valid =
+                matches && text_between(&parts, &bookmark.position, &bookmark.position).is_some();
+            resolutions.push(Resolution {
+                id: bookmark.id,
+                status: if valid {
+                    Status::Resolved
+                } else {
+                    Status::Stale
+                },
+            });
+        }
+        let ranges = self
+            .highlights
+            .iter()
+            .filter(|h| !h.deleted)
+            .map(|h| (h.id, &h.start, &h.end, &h.quote))
+            .chain(
+                self.notes
+                    .iter()
+                    .filter(|n| !n.deleted)
+                    .map(|n| (n.id, &n.start, &n.end, &n.quote)),
+            );
+        for (id, start, end, quote) in ranges {
+            let status = if !matches {
+                Status::Stale
+            } else if text_between(&parts, start, end).as_ref() == Some(quote) {
+                Status::Resolved
+            } else if let Some((start, end)) = find_quote(&parts, quote, start) {
+                Status::Relocated { start, end }
+            } else {
+                Status::Stale
+            };
+            resolutions.push(Resolution { id, status });
+        }
+        resolutions
+    }
+
+    /// Merges annotations from another device, the last modification of every annotation wins.
+    /// Returns `false` if the annotations are for another book.
+    pub fn merge(&mut self, other: Annotations) -> bool {
+        if self.book_id != other.book_id {
+            return false;
+        }
+        merge_records(&mut self.bookmarks, other.bookmarks);
+        merge_records(&mut self.highlights, other.highlights);
+        merge_records(&mut self.notes, other.notes);
+        true
+    }
+}
+
+trait Record: Ord {
+    fn id(&self) -> Uuid;
+    fn modified(&self) -> DateTime<Utc>;
+    fn deleted(&self) -> bool;
+    fn position(&self) -> &Position;
+}
+
+macro_rules! record {
+    ($type:ty, $position:ident) => {
+        impl Record for $type {
+            fn id(&self) -> Uuid {
+                self.id
+            }
+
+            fn modified(&self) -> DateTime<Utc> {
+                self.modified
+            }
+
+            fn deleted(&self) -> bool {
+                self.deleted
+            }
+
+            fn position(&self) -> &Position {
+                &self.$position
+            }
+        }
+    };
+}
+
+record!(Bookmark, position);
+record!(Highlight, start);
+record!(UserNote, start);
+
+// the result does not depend on the order of merging, so every device ends up with the same annotations
+fn merge_records<T: Record>(records: &mut Vec<T>, other: Vec<T>) {
+    let mut merged: HashMap<Uuid, T> = HashMap::new();
+    for record in records.drain(..).chain(other) {
+        match merged.get(&record.id()) {
+            Some(existing)
+                if (existing.modified(), existing.deleted(), existing)
+                    >= (record.modified(), record.deleted(), &record) => {}
+            _ => {
+                merged.insert(record.id(), record);
+            }
+        }
+    }
+    records.extend(merged.into_values());
+    records.sort_by(|a, b| a.position().cmp(b.position()).then(a.id().cmp(&b.id())));
+}
+
+// the occurrence of the quote closest to the old position, preferring its part
+fn find_quote(parts: &[PartBlocks], quote: &str, near: &Position) -> Option<(Position, Position)> {
+    let quote = quote.chars().collect::<Vec<_>>();
+    if quote.is_empty() {
+        return None;
+    }
+    let mut best: Option<((bool, usize), Position, Position)> = None;
+    for part in parts {
+        let mut chars = vec![];
+        let mut starts = vec![];
+        for block in &part.blocks {
+            if !starts.is_empty() {
+                chars.push('\n');
+            }
+            starts.push(chars.len());
+            chars.extend(block.text().chars());
+        }
+        let position = |offset: usize| {
+            let block = starts.partition_point(|&s| s <= offset) - 1;
+            Position::new(part.part.clone(), block, offset - starts[block])
+        };
+        for i in (0..chars.len()).filter(|&i| chars[i..].starts_with(&quote)) {
+            let start = position(i);
+            let end = position(i + quote.len());
+            let distance = (part.part != near.part, start.block.abs_diff(near.block));
+            if best.as_ref().is_none_or(|(d, _, _)| distance < *d) {
+                best = Some((distance, start, end));
+            }
+        }
+    }
+    best.map(|(_, start, end)| (start, end))
+}
@@ -0,0 +1,48 @@
+use crate::ContributorRole;
+
+const ROLES: [ContributorRole; 8] = [
+    ContributorRole::Translator,
+    ContributorRole::Editor,
+    ContributorRole::Illustrator,
+    ContributorRole::Narrator,
+    ContributorRole::Compiler,
+    ContributorRole::BookProducer,
+    ContributorRole::Publisher,
+    ContributorRole::Contributor,
+];
+
+impl ContributorRole {
+    /// MARC relator code of the role, as used by OPF and ONIX and in JSON
+    pub fn marc_code(self) -> &'static str {
+        match self {
+            ContributorRole::Translator => "trl",
+            ContributorRole::Editor => "edt",
+            ContributorRole::Illustrator => "ill",
+            ContributorRole::Narrator => "nrt",
+            ContributorRole::Compiler => "com",
+            ContributorRole::BookProducer => "bkp",
+            ContributorRole::Publisher => "pbl",
+            ContributorRole::Contributor => "ctb",
+        }
+    }
+
+    /// Role of the MARC relator code, `None` for codes of other roles
+    pub fn from_marc_code(code: &str) -> Option<ContributorRole> {
+        ROLES.into_iter().find(|r| r.marc_code() == code)
+    }
+}
+
+impl TryFrom<String> for ContributorRole {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        ContributorRole::from_marc_code(&value)
+            .ok_or_else(|| format!("unknown MARC relator code: {value}"))
+    }
+}
+
+impl From<ContributorRole> for &'static str {
+    fn from(value: ContributorRole) -> Self {
+        value.marc_code()
+    }
+}
@@ -0,0 +1,56 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use uuid::Uuid;
+
+use crate::walk::for_each_image_id_mut;
+use crate::Book;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImageDedupReport {
+    /// Canonical id of every duplicate image
+    pub replaced: HashMap<Uuid, Uuid>,
+    /// Size of the data of the duplicates
+    pub bytes_saved: usize,
+}
+
+impl Book {
+    /// Points all images with identical data to a single id, the smallest one among them,
+    /// and drops the manifest entries of the duplicates. The data of the images is taken
+    /// by their ids, images without data are left as they are.
+    pub fn deduplicate_images(&mut self, resources: &HashMap<Uuid, Vec<u8>>) -> ImageDedupReport {
+        let mut ids: Vec<&Uuid> = resources.keys().collect();
+        ids.sort();
+        let mut canonical: HashMap<u64, Vec<Uuid>> = HashMap::new();
+        let mut report = ImageDedupReport::default();
+        for id in ids {
+            let data = &resources[id];
+            let mut hasher = DefaultHasher::new();
+            data.hash(&mut hasher);
+            // the data is compared as well, in case of a hash collision
+            let candidates = canonical.entry(hasher.finish()).or_default();
+            match candidates.iter().find(|c| resources[*c] == *data) {
+                Some(original) => {
+                    report.replaced.insert(*id, *original);
+                    report.bytes_saved += data.len();
+                }
+                None => candidates.push(*id),
+            }
+        }
+        if report.replaced.is_empty() {
+            return report;
+        }
+        for_each_image_id_mut(self, &mut |id| {
+            if let Some(original) = report.replaced.get(id) {
+                *id = *original;
+            }
+        });
+        for (duplicate, original) in &report.replaced {
+            if let Some(resource) = self.resources.remove(duplicate) {
+                self.resources.entry(*original).or_insert(resource);
+            }
+        }
+        report
+    }
+}
@@ -0,0 +1,466 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use similar::{capture_diff_slices, Algorithm, ChangeTag, DiffTag, TextDiff};
+
+use crate::toc::title_text;
+use crate::walk::{parts, BlockKind};
+use crate::{Book, BookIdentifier, Date, Part, Position, Sequence};
+
+/// Blocks and chapters less similar than this are not considered edits of each other
+const MIN_SIMILARITY: f32 = 0.5;
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct BookDiff {
+    pub metadata: Vec<MetadataChange>,
+    pub chapters: Vec<ChapterChange>,
+    /// Text blocks of the annotation, the front matter and the chapters
+    pub blocks: Vec<BlockChange>,
+    pub footnotes: Vec<FootnoteChange>,
+}
+
+impl BookDiff {
+    pub fn is_empty(&self) -> bool {
+        self.metadata.is_empty()
+            && self.chapters.is_empty()
+            && self.blocks.is_empty()
+            && self.footnotes.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct MetadataChange {
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub enum ChapterChange {
+    Inserted { new: Part, title: Option<String> },
+    Deleted { old: Part, title: Option<String> },
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub enum BlockChange {
+    Inserted {
+        new: Position,
+        text: String,
+    },
+    Deleted {
+        old: Position,
+        text: String,
+    },
+    /// The same text in another place, possibly in another chapter
+    Moved {
+        old: Position,
+        new: Position,
+        text: String,
+    },
+    Edited {
+        old: Position,
+        new: Position,
+        changes: Vec<TextChange>,
+    },
+}
+
+/// Notes and comments are matched by their ids
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub enum FootnoteChange {
+    Inserted(Part),
+    Deleted(Part),
+    Edited {
+        part: Part,
+        changes: Vec<TextChange>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum TextChange {
+    Equal(String),
+    Deleted(String),
+    Inserted(String),
+}
+
+/// Compares two versions of a book, aligning chapters and blocks by anchors where available
+/// and by the similarity of the text otherwise
+pub fn diff(old: &Book, new: &Book) -> BookDiff {
+    let (old_parts, old_footnotes) = collect(old);
+    let (new_parts, new_footnotes) = collect(new);
+
+    let mut chapters = vec![];
+    let mut edited = vec![];
+    let mut deleted = vec![];
+    let mut inserted = vec![];
+    let old_texts = old_parts.iter().map(DiffPart::text).collect::<Vec<_>>();
+    let new_texts = new_parts.iter().map(DiffPart::text).collect::<Vec<_>>();
+    let alignment = align(&keys(&old_parts), &keys(&new_parts), |o, n| {
+        similarity(&old_texts[o], &new_texts[n])
+    });
+    for &o in &alignment.deleted {
+        let part = &old_parts[o];
+        if let Part::Chapter(_) = part.part {
+            chapters.push(ChapterChange::Deleted {
+                old: part.part.clone(),
+                title: part.title.clone(),
+            });
+        }
+        deleted.extend(&part.blocks);
+    }
+    for &n in &alignment.inserted {
+        let part = &new_parts[n];
+        if let Part::Chapter(_) = part.part {
+            chapters.push(ChapterChange::Inserted {
+                new: part.part.clone(),
+                title: part.title.clone(),
+            });
+        }
+        inserted.extend(&part.blocks);
+    }
+    for (o, n) in alignment.pairs {
+        let (old_blocks, new_blocks) = (&old_parts[o].blocks, &new_parts[n].blocks);
+        let blocks = align(&keys(old_blocks), &keys(new_blocks), |o, n| {
+            similarity(&old_blocks[o].text, &new_blocks[n].text)
+        });
+        for (o, n) in blocks.pairs {
+            let (old, new) = (&old_blocks[o], &new_blocks[n]);
+            if old.text != new.text {
+                edited.push(BlockChange::Edited {
+                    old: old.position.clone(),
+                    new: new.position.clone(),
+                    changes: text_changes(&old.text, &new.text),
+                });
+            }
+        }
+        deleted.extend(blocks.deleted.iter().map(|&o| &old_blocks[o]));
+        inserted.extend(blocks.inserted.iter().map(|&n| &new_blocks[n]));
+    }
+
+    deleted.sort_by(|a, b| a.position.cmp(&b.position));
+    inserted.sort_by(|a, b| a.position.cmp(&b.position));
+    let mut blocks = edited;
+    deleted.retain(|old| {
+        let Some(i) = inserted.iter().position(|new| new.text == old.text) else {
+            return true;
+        };
+        let new = inserted.remove(i);
+        blocks.push(BlockChange::Moved {
+            old: old.position.clone(),
+            new: new.position.clone(),
+            text: new.text.clone(),
+        });
+        false
+    });
+    blocks.extend(deleted.into_iter().map(|old| BlockChange::Deleted {
+        old: old.position.clone(),
+        text: old.text.clone(),
+    }));
+    blocks.extend(inserted.into_iter().map(|new| BlockChange::Inserted {
+        new: new.position.clone(),
+        text: new.text.clone(),
+    }));
+
+    BookDiff {
+        metadata: metadata(old, new),
+        chapters,
+        blocks,
+        footnotes: footnotes(&old_footnotes, &new_footnotes),
+    }
+}
+
+struct DiffPart {
+    part: Part,
+    anchor: Option<String>,
+    title: Option<String>,
+    blocks: Vec<DiffBlock>,
+}
+
+struct DiffBlock {
+    position: Position,
+    anchor: Option<String>,
+    text: String,
+}
+
+impl DiffPart {
+    fn text(&self) -> String {
+        let texts = self.blocks.iter().map(|b| b.text.as_str());
+        texts.collect::<Vec<_>>().join("\n")
+    }
+}
+
+// the parts in reading order and the texts of notes and comments
+fn collect(book: &Book) -> (Vec<DiffPart>, BTreeMap<Part, String>) {
+    let mut diff_parts = vec![];
+    let mut footnotes = BTreeMap::new();
+    for part in parts(book) {
+        let blocks = part
+            .blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, b)| {
+                matches!(
+                    b.kind,
+                    BlockKind::Text(_) | BlockKind::Verbatim(_) | BlockKind::Math(_)
+                )
+            })
+            .map(|(i, b)| DiffBlock {
+                position: Position::new(part.part.clone(), i, 0),
+                anchor: b.anchor.map(str::to_string),
+                text: b.text(),
+            })
+            .filter(|b| !b.text.is_empty())
+            .collect();
+        let diff_part = DiffPart {
+            title: match &part.part {
+                Part::Chapter(path) => book
+                    .chapter(path)
+                    .and_then(|c| c.title.as_ref())
+                    .map(title_text)
+                    .filter(|t| !t.is_empty()),
+                _ => None,
+            },
+            part: part.part,
+            anchor: part.anchor.map(str::to_string),
+            blocks,
+        };
+        match diff_part.part {
+            Part::Note(_) | Part::Comment(_) => {
+                footnotes.insert(diff_part.part.clone(), diff_part.text());
+            }
+            Part::Annotation | Part::Front | Part::Chapter(_) => diff_parts.push(diff_part),
+        }
+    }
+    (diff_parts, footnotes)
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+enum Key {
+    Part(Part),
+    Anchor(String),
+    Text(String),
+}
+
+trait Keyed {
+    fn key(&self) -> Key;
+}
+
+impl Keyed for DiffPart {
+    fn key(&self) -> Key {
+        match (&self.part, &self.anchor, &self.title) {
+            (Part::Chapter(_), Some(anchor), _) => Key::Anchor(anchor.clone()),
+            (Part::Chapter(_), None, Some(title)) => Key::Text(title.clone()),
+            (Part::Chapter(_), None, None) => Key::Text(self.text()),
+            (part, _, _) => Key::Part(part.clone()),
+        }
+    }
+}
+
+impl Keyed for DiffBlock {
+    fn key(&self) -> Key {
+        match &self.anchor {
+            Some(anchor) => Key::Anchor(anchor.clone()),
+            None => Key::Text(self.text.clone()),
+        }
+    }
+}
+
+fn keys(items: &[impl Keyed]) -> Vec<Key> {
+    items.iter().map(Keyed::key).collect()
+}
+
+#[derive(Default)]
+struct Alignment {
+    pairs: Vec<(usize, usize)>,
+    deleted: Vec<usize>,
+    inserted: Vec<usize>,
+}
+
+// pairs equal keys and then the most similar items in between, keeping the order
+fn align(old: &[Key], new: &[Key], similarity: impl Fn(usize, usize) -> f32) -> Alignment {
+    let mut alignment = Alignment::default();
+    for op in capture_diff_slices(Algorithm::Patience, old, new) {
+        let (old_range, new_range) = (op.old_range(), op.new_range());
+        if op.tag() == DiffTag::Equal {
+            alignment.pairs.extend(old_range.zip(new_range));
+            continue;
+        }
+        let mut next = new_range.start;
+        for o in old_range {
+            let best = (next..new_range.end)
+                .map(|n| (n, similarity(o, n)))
+                .filter(|&(_, s)| s >= MIN_SIMILARITY)
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            if let Some((n, _)) = best {
+                alignment.inserted.extend(next..n);
+                alignment.pairs.push((o, n));
+                next = n + 1;
+            } else {
+                alignment.deleted.push(o);
+            }
+        }
+        alignment.inserted.extend(next..new_range.end);
+    }
+    alignment
+}
+
+fn similarity(old: &str, new: &str) -> f32 {
+    TextDiff::from_words(old, new).ratio()
+}
+
+fn text_changes(old: &str, new: &str) -> Vec<TextChange> {
+    let mut changes: Vec<TextChange> = vec![];
+    for change in TextDiff::from_words(old, new).iter_all_changes() {
+        let value = change.value();
+        match (changes.last_mut(), change.tag()) {
+            (Some(TextChange::Equal(text)), ChangeTag::Equal)
+            | (Some(TextChange::Deleted(text)), ChangeTag::Delete)
+            | (Some(TextChange::Inserted(text)), ChangeTag::Insert) => text.push_str(value),
+            (_, ChangeTag::Equal) => changes.push(TextChange::Equal(value.to_string())),
+            (_, ChangeTag::Delete) => changes.push(TextChange::Deleted(value.to_string())),
+            (_, ChangeTag::Insert) => changes.push(TextChange::Inserted(value.to_string())),
+        }
+    }
+    changes
+}
+
+fn footnotes(old: &BTreeMap<Part, String>, new: &BTreeMap<Part, String>) -> Vec<FootnoteChange> {
+    let mut changes = vec![];
+    for (part, old_text) in old {
+        match new.get(part) {
+            None => changes.push(FootnoteChange::Deleted(part.clone())),
+            Some(new_text) if new_text != old_text => changes.push(FootnoteChange::Edited {
+                part: part.clone(),
+                changes: text_changes(old_text, new_text),
+            }),
+            Some(_) => {}
+        }
+    }
+    for part in new.keys().filter(|p| !old.contains_key(p)) {
+        changes.push(FootnoteChange::Inserted(part.clone()));
+    }
+    changes
+}
+
+fn metadata(old: &Book, new: &Book) -> Vec<MetadataChange> {
+    let mut changes = vec![];
+    let mut compare = |field: &str, old: Option<String>, new: Option<String>| {
+        if old != new {
+            changes.push(MetadataChange {
+                field: field.to_string(),
+                old,
+                new,
+            });
+        }
+    };
+    compare(
+        "language",
+        old.language.as_ref().map(|l| l.to_string()),
+        new.language.as_ref().map(|l| l.to_string()),
+    );
+    compare(
+        "short_title",
+        Some(old.short_title.clone()),
+        Some(new.short_title.clone()),
+    );
+    compare(
+        "date.iso_date",
+        old.date.iso_date.map(|d| d.to_string()),
+        new.date.iso_date.map(|d| d.to_string()),
+    );
+    compare(
+        "date.display_date",
+        old.date.display_date.clone(),
+        new.date.display_date.clone(),
+    );
+    let authors = |book: &Book| {
+        let names = book.authors.iter().map(|a| a.full_name.as_str());
+        Some(names.collect::<Vec<_>>().join(", ")).filter(|a| !a.is_empty())
+    };
+    compare("authors", authors(old), authors(new));
+    let contributors = |book: &Book| {
+        let names = book.contributors.iter().map(|c| {
+            let roles = c.roles.iter().map(|r| r.marc_code());
+            format!(
+                "{} ({})",
+                c.person.full_name,
+                roles.collect::<Vec<_>>().join(", ")
+            )
+        });
+        Some(names.collect::<Vec<_>>().join(", ")).filter(|c| !c.is_empty())
+    };
+    compare("contributors", contributors(old), contributors(new));
+    let identifiers = |book: &Book| {
+        let ids = book.identifiers.iter().map(|i| match i {
+            BookIdentifier::Isbn(isbn) => format!("ISBN {isbn}"),
+            BookIdentifier::Other { scheme, value } => format!("{scheme} {value}"),
+        });
+        Some(ids.collect::<Vec<_>>().join(", ")).filter(|i| !i.is_empty())
+    };
+    compare("identifiers", identifiers(old), identifiers(new));
+    compare(
+        "publication.publisher",
+        old.publication.as_ref().and_then(|p| p.publisher.clone()),
+        new.publication.as_ref().and_then(|p| p.publisher.clone()),
+    );
+    compare(
+        "publication.city",
+        old.publication.as_ref().and_then(|p| p.city.clone()),
+        new.publication.as_ref().and_then(|p| p.city.clone()),
+    );
+    compare(
+        "publication.date.iso_date",
+        publication_date(old)
+            .and_then(|d| d.iso_date)
+            .map(|d| d.to_string()),
+        publication_date(new)
+            .and_then(|d| d.iso_date)
+            .map(|d| d.to_string()),
+    );
+    compare(
+        "publication.date.display_date",
+        publication_date(old).and_then(|d| d.display_date.clone()),
+        publication_date(new).and_then(|d| d.display_date.clone()),
+    );
+    let copyright_holders = |book: &Book| {
+        let holders = book.rights.iter().flat_map(|r| &r.copyright_holders);
+        Some(holders.cloned().collect::<Vec<_>>().join(", ")).filter(|h| !h.is_empty())
+    };
+    compare(
+        "rights.copyright_holders",
+        copyright_holders(old),
+        copyright_holders(new),
+    );
+    compare(
+        "rights.license",
+        old.rights.as_ref().and_then(|r| r.license.clone()),
+        new.rights.as_ref().and_then(|r| r.license.clone()),
+    );
+    let sequences = |book: &Book| {
+        let names = book.sequences.iter().map(sequence_name);
+        Some(names.collect::<Vec<_>>().join(", ")).filter(|s| !s.is_empty())
+    };
+    compare("sequences", sequences(old), sequences(new));
+    compare(
+        "cover",
+        old.cover.as_ref().map(|c| c.id.to_string()),
+        new.cover.as_ref().map(|c| c.id.to_string()),
+    );
+    changes
+}
+
+fn publication_date(book: &Book) -> Option<&Date> {
+    book.publication.as_ref().and_then(|p| p.date.as_ref())
+}
+
+// "Name 3 / Nested 1" along the first nested sequences
+fn sequence_name(sequence: &Sequence) -> String {
+    let mut name = sequence.name.clone();
+    if let Some(number) = sequence.number {
+        name.push_str(&format!(" {number}"));
+    }
+    if let Some(nested) = sequence.sequences.first() {
+        name.push_str(" / ");
+        name.push_str(&sequence_name(nested));
+    }
+    name
+}
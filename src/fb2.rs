@@ -1,15 +1,63 @@
 use std::collections::{HashMap, HashSet};
 
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 use uuid::Uuid;
 
+use crate::placeholder::dominant_color;
+use crate::walk::{plain_text, MAX_COLUMN_SPAN, MAX_ROW_SPAN};
 use crate::{
-    Annotation, AnnotationElement, Author, BaselineShift, Book, Chapter, Cite, CiteElement,
-    Content, Date, Epigraph, EpigraphElement, FontStyle, Footnote, FootnoteKind, FootnoteLink,
-    Footnotes, Href, Image, InlineImage, Link, Paragraph, Poem, PoemElement, Span, Stanza, Table,
-    TableCell, TableRow, Text, TextDecoration, Title, TitleElement,
+    Annotation, AnnotationElement, Author, BaselineShift, Book, BookIdentifier, Chapter, Cite,
+    CiteElement, Content, Contributor, ContributorRole, Date, Epigraph, EpigraphElement, FontStyle,
+    Footnote, FootnoteKind, FootnoteLink, Footnotes, HorizontalAlign, Href, Image, InlineImage,
+    Isbn, Link, Paragraph, Placeholder, Poem, PoemElement, Preformatted, Publication, Rights,
+    Sequence, Span, Stanza, Table, TableCell, TableRow, Text, TextDecoration, Title, TitleElement,
+    VerticalAlign,
 };
 
 const BOLD_WEIGHT: u16 = 600;
+/// Types of FB2 custom-info that hold the license of the book
+const LICENSE_INFO_TYPES: [&str; 4] = ["license", "licence", "rights", "copyright"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fb2Options {
+    /// Applies [`Book::normalize_spans`] to the imported book
+    pub normalize_spans: bool,
+    /// Semantics of FB2 named styles by their lowercase name.
+    /// Styles without a mapping are kept in [`Text::classes`].
+    pub named_styles: HashMap<String, Fb2Style>,
+}
+
+impl Default for Fb2Options {
+    fn default() -> Self {
+        let named_styles = [
+            ("underline", Fb2Style::Decoration(TextDecoration::Underline)),
+            ("u", Fb2Style::Decoration(TextDecoration::Underline)),
+            ("highlight", Fb2Style::Decoration(TextDecoration::Highlight)),
+            ("mark", Fb2Style::Decoration(TextDecoration::Highlight)),
+            ("marker", Fb2Style::Decoration(TextDecoration::Highlight)),
+            ("small-caps", Fb2Style::FontStyle(FontStyle::SmallCaps)),
+            ("smallcaps", Fb2Style::FontStyle(FontStyle::SmallCaps)),
+            ("sc", Fb2Style::FontStyle(FontStyle::SmallCaps)),
+        ]
+        .into_iter()
+        .map(|(name, style)| (name.to_string(), style))
+        .collect();
+        Fb2Options {
+            normalize_spans: false,
+            named_styles,
+        }
+    }
+}
+
+/// Semantics an FB2 named style is imported as
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Fb2Style {
+    Bold,
+    FontStyle(FontStyle),
+    Decoration(TextDecoration),
+    BaselineShift(BaselineShift),
+}
 
 impl Book {
     pub fn from_fb2(
@@ -17,6 +65,16 @@ impl Book {
         book_id: Uuid,
         binary_ids: &HashMap<String, Uuid>,
     ) -> Book {
+        Book::from_fb2_with_options(book, book_id, binary_ids, &Fb2Options::default())
+    }
+
+    pub fn from_fb2_with_options(
+        book: fb2::FictionBook,
+        book_id: Uuid,
+        binary_ids: &HashMap<String, Uuid>,
+        options: &Fb2Options,
+    ) -> Book {
+        let binaries = Binaries::from_fb2(book.binaries, binary_ids);
         let short_title = book.description.title_info.book_title.value;
         let date = book
             .description
@@ -32,7 +90,36 @@ impl Book {
             .title_info
             .authors
             .into_iter()
-            .filter_map(|a| Author::from_fb2(a))
+            .filter_map(Author::from_fb2)
+            .collect();
+        let document_info = book.description.document_info;
+        let publish_info = book.description.publish_info;
+        let identifiers = BookIdentifier::from_fb2(publish_info.as_ref(), document_info.as_ref());
+        let publication = publish_info.as_ref().and_then(Publication::from_fb2);
+        let rights = Rights::from_fb2(document_info.as_ref(), &book.description.custom_info);
+        let translators = book
+            .description
+            .title_info
+            .translators
+            .into_iter()
+            .map(|a| (a, ContributorRole::Translator));
+        let producers = document_info
+            .into_iter()
+            .flat_map(|d| d.authors)
+            .map(|a| (a, ContributorRole::BookProducer));
+        let contributors = Contributor::from_fb2(translators.chain(producers));
+        let sequences = book
+            .description
+            .title_info
+            .sequences
+            .into_iter()
+            .flat_map(|s| Sequence::from_fb2(s, false))
+            .chain(
+                publish_info
+                    .into_iter()
+                    .flat_map(|p| p.sequences)
+                    .flat_map(|s| Sequence::from_fb2(s, true)),
+            )
             .collect();
         let language = non_empty(book.description.title_info.lang).and_then(|l| l.parse().ok());
         let cover = book
@@ -41,7 +128,7 @@ impl Book {
             .cover_page
             .into_iter()
             .flat_map(|c| c.images)
-            .filter_map(|i| InlineImage::from_fb2(i, binary_ids))
+            .filter_map(|i| InlineImage::from_fb2(i, &binaries))
             .next();
 
         let mut bodies: HashMap<Option<String>, Vec<fb2::Body>> = HashMap::new();
@@ -58,7 +145,8 @@ impl Book {
                 Footnotes::from_fb2(
                     b,
                     &Context {
-                        binaries: binary_ids,
+                        binaries: &binaries,
+                        named_styles: &options.named_styles,
                         notes: HashSet::new(),
                         comments: HashSet::new(),
                     },
@@ -73,7 +161,8 @@ impl Book {
                 Footnotes::from_fb2(
                     b,
                     &Context {
-                        binaries: binary_ids,
+                        binaries: &binaries,
+                        named_styles: &options.named_styles,
                         notes: HashSet::new(),
                         comments: HashSet::new(),
                     },
@@ -89,7 +178,8 @@ impl Book {
             .map(|c| c.content.keys().map(|k| k.to_string()).collect())
             .unwrap_or_default();
         let ctx = Context {
-            binaries: binary_ids,
+            binaries: &binaries,
+            named_styles: &options.named_styles,
             notes: note_ids,
             comments: comment_ids,
         };
@@ -118,12 +208,17 @@ impl Book {
             (vec![], language, None, vec![])
         };
 
-        Book {
+        let mut book = Book {
             id: book_id,
             language,
             short_title,
             date,
             authors,
+            contributors,
+            identifiers,
+            publication,
+            rights,
+            sequences,
             cover,
             annotation,
             title,
@@ -131,16 +226,187 @@ impl Book {
             chapters,
             notes,
             comments,
+            resources: HashMap::new(),
+        };
+        if options.normalize_spans {
+            book.normalize_spans();
         }
+        book
     }
 }
 
 struct Context<'a> {
-    binaries: &'a HashMap<String, Uuid>,
+    binaries: &'a Binaries<'a>,
+    named_styles: &'a HashMap<String, Fb2Style>,
     notes: HashSet<String>,
     comments: HashSet<String>,
 }
 
+struct Binaries<'a> {
+    ids: &'a HashMap<String, Uuid>,
+    metadata: HashMap<String, Metadata>,
+}
+
+#[derive(Clone, Default)]
+struct Metadata {
+    width: Option<u32>,
+    height: Option<u32>,
+    mime_type: Option<String>,
+    placeholder: Option<Placeholder>,
+}
+
+impl<'a> Binaries<'a> {
+    fn from_fb2(binaries: Vec<fb2::Binary>, ids: &'a HashMap<String, Uuid>) -> Binaries<'a> {
+        let metadata = binaries
+            .into_iter()
+            .filter(|b| ids.contains_key(&b.id))
+            .map(|b| {
+                // base64 in FB2 is usually split into lines
+                let content: String = b.content.split_whitespace().collect();
+                let data = BASE64.decode(content).unwrap_or_default();
+                let format = image::guess_format(&data).ok();
+                let decoded =
+                    format.and_then(|f| image::load_from_memory_with_format(&data, f).ok());
+                let (width, height) = decoded.as_ref().map(|i| (i.width(), i.height())).unzip();
+                let mime_type = non_empty(b.content_type)
+                    .or_else(|| format.map(|f| f.to_mime_type().to_string()));
+                let metadata = Metadata {
+                    width,
+                    height,
+                    mime_type,
+                    placeholder: decoded
+                        .as_ref()
+                        .and_then(dominant_color)
+                        .map(Placeholder::Color),
+                };
+                (b.id, metadata)
+            })
+            .collect();
+        Binaries { ids, metadata }
+    }
+
+    fn get(&self, id: &str) -> Option<(Uuid, Metadata)> {
+        let uuid = self.ids.get(id)?;
+        Some((*uuid, self.metadata.get(id).cloned().unwrap_or_default()))
+    }
+
+    fn get_href(&self, href: Option<String>) -> Option<(Uuid, Metadata)> {
+        self.get(href.and_then(non_empty)?.strip_prefix('#')?)
+    }
+}
+
+impl Contributor {
+    /// The same person in several roles becomes a single contributor
+    fn from_fb2(authors: impl Iterator<Item = (fb2::Author, ContributorRole)>) -> Vec<Contributor> {
+        let mut contributors: Vec<Contributor> = vec![];
+        for (author, role) in authors {
+            let Some(person) = Author::from_fb2(author) else {
+                continue;
+            };
+            match contributors.iter_mut().find(|c| c.person == person) {
+                Some(contributor) => {
+                    if !contributor.roles.contains(&role) {
+                        contributor.roles.push(role);
+                    }
+                }
+                None => contributors.push(Contributor {
+                    person,
+                    roles: vec![role],
+                }),
+            }
+        }
+        contributors
+    }
+}
+
+impl BookIdentifier {
+    fn from_fb2(
+        publish_info: Option<&fb2::PublishInfo>,
+        document_info: Option<&fb2::DocumentInfo>,
+    ) -> Vec<BookIdentifier> {
+        // the field often lists the ISBNs of several volumes or bindings
+        let isbns = publish_info
+            .and_then(|p| p.isbn.as_ref())
+            .into_iter()
+            .flat_map(|i| i.value.split([',', ';']))
+            .filter_map(Isbn::parse)
+            .map(BookIdentifier::Isbn);
+        let document = document_info
+            .and_then(|d| d.id.clone())
+            .and_then(non_empty)
+            .map(|value| BookIdentifier::Other {
+                scheme: "fb2".to_string(),
+                value,
+            });
+        isbns.chain(document).collect()
+    }
+}
+
+impl Publication {
+    fn from_fb2(value: &fb2::PublishInfo) -> Option<Publication> {
+        let text = |t: &Option<fb2::LocalizedText>| {
+            t.as_ref()
+                .map(|t| t.value.trim().to_string())
+                .and_then(non_empty)
+        };
+        let publication = Publication {
+            publisher: text(&value.publisher),
+            city: text(&value.city),
+            // FB2 gives the year only, which is not a full ISO date
+            date: value.year.map(|year| Date {
+                iso_date: None,
+                display_date: Some(year.to_string()),
+            }),
+        };
+        (publication != Publication::default()).then_some(publication)
+    }
+}
+
+impl Rights {
+    fn from_fb2(
+        document_info: Option<&fb2::DocumentInfo>,
+        custom_info: &[fb2::CustomInfo],
+    ) -> Option<Rights> {
+        let copyright_holders = document_info
+            .into_iter()
+            .flat_map(|d| d.publishers.iter().cloned())
+            .filter_map(Author::from_fb2)
+            .map(|a| a.full_name)
+            .collect();
+        let license = custom_info
+            .iter()
+            .filter(|i| LICENSE_INFO_TYPES.contains(&i.info_type.trim().to_lowercase().as_str()))
+            .map(|i| i.content.trim().to_string())
+            .find(|c| !c.is_empty());
+        let rights = Rights {
+            copyright_holders,
+            license,
+        };
+        (rights != Rights::default()).then_some(rights)
+    }
+}
+
+impl Sequence {
+    /// A sequence without a name is dropped, and its sequences take its place
+    fn from_fb2(value: fb2::Sequence, publisher: bool) -> Vec<Sequence> {
+        let sequences = value
+            .sequences
+            .into_iter()
+            .flat_map(|s| Sequence::from_fb2(s, publisher))
+            .collect();
+        match value.name.map(|n| n.trim().to_string()).and_then(non_empty) {
+            Some(name) => vec![Sequence {
+                name,
+                number: value.number.and_then(|n| u32::try_from(n).ok()),
+                language: value.lang,
+                publisher,
+                sequences,
+            }],
+            None => sequences,
+        }
+    }
+}
+
 impl Footnotes {
     fn from_fb2(body: fb2::Body, ctx: &Context) -> Option<Footnotes> {
         let content = body
@@ -165,6 +431,7 @@ impl Footnote {
             .into_iter()
             .filter_map(|c| Content::from_fb2(c, ctx))
             .collect::<Vec<_>>();
+        let content = scene_breaks(group_preformatted(content));
         if content.is_empty() {
             return None;
         }
@@ -206,17 +473,13 @@ impl Author {
             Some(full_name)
         };
 
-        if let Some(full_name) = full_name {
-            Some(Author {
-                id: Uuid::nil(),
-                full_name,
-                given_name,
-                family_name,
-                middle_name,
-            })
-        } else {
-            None
-        }
+        full_name.map(|full_name| Author {
+            id: Uuid::nil(),
+            full_name,
+            given_name,
+            family_name,
+            middle_name,
+        })
     }
 }
 
@@ -229,6 +492,7 @@ impl Chapter {
             .into_iter()
             .filter_map(|part| Content::from_fb2(part, ctx))
             .collect::<Vec<_>>();
+        let content = scene_breaks(group_preformatted(content));
         let sub_chapters = section_content
             .sections
             .into_iter()
@@ -253,6 +517,8 @@ impl Chapter {
 
         Some(Chapter {
             anchor: section.id,
+            language: section.lang,
+            direction: None,
             title,
             annotation,
             cover,
@@ -277,6 +543,107 @@ impl Content {
     }
 }
 
+// FB2 has no code blocks, so listings are paragraphs of code, one per line
+fn group_preformatted(content: Vec<Content>) -> Vec<Content> {
+    let mut grouped = vec![];
+    for content in content {
+        let paragraph = match content {
+            Content::Paragraph(p) if is_code(&p) => p,
+            content => {
+                grouped.push(content);
+                continue;
+            }
+        };
+        let line = paragraph
+            .content
+            .into_iter()
+            .map(|s| match s {
+                Span::Text(t) => t.value,
+                _ => String::new(),
+            })
+            .collect();
+        match grouped.last_mut() {
+            // an anchor starts a new block, so that links to it still work
+            Some(Content::Preformatted(p)) if paragraph.anchor.is_none() => {
+                p.classes.extend(paragraph.classes);
+                p.lines.push(line);
+            }
+            _ => grouped.push(Content::Preformatted(Preformatted {
+                anchor: paragraph.anchor,
+                classes: paragraph.classes,
+                language: None,
+                lines: vec![line],
+            })),
+        }
+    }
+    grouped
+}
+
+// separators of scenes are paragraphs like `* * *`, or several empty lines in a row,
+// separators with an anchor stay paragraphs, so that links to them keep working
+fn scene_breaks(content: Vec<Content>) -> Vec<Content> {
+    let mut result = vec![];
+    let mut empty_lines = 0;
+    for content in content {
+        let content = match content {
+            Content::Paragraph(p) | Content::Subtitle(p)
+                if p.anchor.is_none() && is_separator(&p) =>
+            {
+                Content::SceneBreak
+            }
+            Content::EmptyLine => {
+                empty_lines += 1;
+                continue;
+            }
+            content => content,
+        };
+        // empty lines around a separator are part of it
+        let after_break = matches!(result.last(), Some(Content::SceneBreak));
+        if let Content::SceneBreak = content {
+            if !after_break {
+                result.push(content);
+            }
+        } else {
+            if !after_break {
+                if empty_lines > 1 && !result.is_empty() {
+                    result.push(Content::SceneBreak);
+                } else {
+                    result.extend((0..empty_lines).map(|_| Content::EmptyLine));
+                }
+            }
+            result.push(content);
+        }
+        empty_lines = 0;
+    }
+    if !matches!(result.last(), Some(Content::SceneBreak)) {
+        result.extend((0..empty_lines).map(|_| Content::EmptyLine));
+    }
+    result
+}
+
+fn is_separator(paragraph: &Paragraph) -> bool {
+    if !paragraph.content.iter().all(|s| matches!(s, Span::Text(_))) {
+        return false;
+    }
+    let text = plain_text(&paragraph.content);
+    let mut chars = text.chars().filter(|c| !c.is_whitespace());
+    match chars.next() {
+        Some('*') => {
+            let rest = chars.collect::<Vec<_>>();
+            rest.len() >= 2 && rest.iter().all(|&c| c == '*')
+        }
+        Some(c @ ('\u{2014}' | '\u{00A7}' | '\u{2042}')) => chars.all(|other| other == c),
+        _ => false,
+    }
+}
+
+fn is_code(paragraph: &Paragraph) -> bool {
+    paragraph
+        .content
+        .iter()
+        .all(|s| matches!(s, Span::Text(t) if t.font_style.contains(&FontStyle::Code)))
+}
+
 impl Annotation {
     fn from_fb2(value: fb2::Annotation, ctx: &Context) -> Option<Annotation> {
         let content = value
@@ -372,6 +739,7 @@ impl Poem {
             .collect();
         Some(Poem {
             anchor: value.id,
+            classes: HashSet::new(),
             title,
             epigraphs,
             authors,
@@ -426,6 +794,9 @@ impl Cite {
             .collect();
         Some(Cite {
             anchor: value.id,
+            classes: HashSet::new(),
+            language: value.lang,
+            direction: None,
             content,
             authors,
         })
@@ -482,7 +853,13 @@ impl Paragraph {
         if content.is_empty() {
             None
         } else {
-            Some(Paragraph { anchor, content })
+            Some(Paragraph {
+                anchor,
+                classes: classes(paragraph.style),
+                language: paragraph.lang,
+                direction: None,
+                content,
+            })
         }
     }
 }
@@ -512,14 +889,20 @@ impl Table {
 
         let mut rows = vec![];
         for row in value.rows {
+            let row_align = HorizontalAlign::from_fb2(row.align);
             let cells = row
                 .cells
                 .into_iter()
                 .map(|c| match c {
-                    fb2::TableCellElement::Head(h) => h,
-                    fb2::TableCellElement::Data(d) => d,
+                    fb2::TableCellElement::Head(h) => (h, true),
+                    fb2::TableCellElement::Data(d) => (d, false),
+                })
+                .map(|(c, header)| {
+                    let mut cell = TableCell::from_fb2(c, ctx);
+                    cell.header = header;
+                    cell.horizontal_align = cell.horizontal_align.or(row_align);
+                    cell
                 })
-                .map(|c| TableCell::from_fb2(c, ctx))
                 .collect::<Vec<_>>();
             if !cells.is_empty() {
                 rows.push(TableRow { cells });
@@ -532,6 +915,8 @@ impl Table {
 
         Some(Table {
             anchor: value.id,
+            classes: classes(value.style),
+            caption: None,
             header_column,
             header_row,
             rows,
@@ -542,8 +927,21 @@ impl Table {
 impl TableCell {
     // we don't return Option<TableCell> because it can break layout
     fn from_fb2(value: fb2::TableCell, ctx: &Context) -> TableCell {
+        // the defaults of FB2 are the same, so they are left out
+        let span =
+            |span: Option<i32>, max: u32| span.filter(|&s| s > 1).map(|s| (s as u32).min(max));
         TableCell {
             anchor: value.id,
+            classes: classes(value.style),
+            header: false,
+            column_span: span(value.column_span, MAX_COLUMN_SPAN),
+            row_span: span(value.row_span, MAX_ROW_SPAN),
+            horizontal_align: HorizontalAlign::from_fb2(value.horizontal_align),
+            vertical_align: match value.vertical_align {
+                fb2::VerticalAlign::Top => None,
+                fb2::VerticalAlign::Middle => Some(VerticalAlign::Middle),
+                fb2::VerticalAlign::Bottom => Some(VerticalAlign::Bottom),
+            },
             content: value
                 .elements
                 .into_iter()
@@ -553,18 +951,31 @@ impl TableCell {
     }
 }
 
+impl HorizontalAlign {
+    fn from_fb2(value: fb2::HorizontalAlign) -> Option<HorizontalAlign> {
+        match value {
+            fb2::HorizontalAlign::Left => None,
+            fb2::HorizontalAlign::Center => Some(HorizontalAlign::Center),
+            fb2::HorizontalAlign::Right => Some(HorizontalAlign::Right),
+        }
+    }
+}
+
 impl Image {
-    fn from_fb2(value: fb2::Image, binary_ids: &HashMap<String, Uuid>) -> Option<Image> {
-        value
-            .href
-            .and_then(non_empty)
-            .and_then(|href| href.strip_prefix('#').and_then(|href| binary_ids.get(href)))
-            .map(|id| Image {
-                id: id.clone(),
-                anchor: value.id,
-                alt: value.alt,
-                title: value.title,
-            })
+    fn from_fb2(value: fb2::Image, binaries: &Binaries) -> Option<Image> {
+        let (id, metadata) = binaries.get_href(value.href)?;
+        Some(Image {
+            id,
+            anchor: value.id,
+            classes: HashSet::new(),
+            alt: value.alt,
+            title: value.title,
+            width: metadata.width,
+            height: metadata.height,
+            mime_type: metadata.mime_type,
+            placeholder: metadata.placeholder,
+            caption: vec![],
+        })
     }
 }
 
@@ -585,7 +996,13 @@ impl Span {
                     .map(italic_text),
             ),
             fb2::StyleElement::Style(s) => {
-                spans.extend(s.elements.into_iter().flat_map(|e| Span::from_fb2(e, ctx)))
+                let style = ctx.named_styles.get(&s.name.to_ascii_lowercase());
+                spans.extend(
+                    s.elements
+                        .into_iter()
+                        .flat_map(|e| Span::from_fb2(e, ctx))
+                        .map(|span| named_style_text(span, &s.name, style)),
+                )
             }
             fb2::StyleElement::Link(l) => {
                 let href = l.href.and_then(Href::from_fb2);
@@ -599,12 +1016,12 @@ impl Span {
                     let mut text = vec![];
                     for span in content {
                         match span {
-                            Span::Footnote(_) | Span::Link(_) => {}
+                            Span::Footnote(_) | Span::Link(_) | Span::Math(_) | Span::Ruby(_) => {}
                             Span::Image(i) => images.push(Span::Image(i)),
                             Span::Text(t) => text.push(t),
                         }
                     }
-                    if let Some(id) = ctx.binaries.get(href.as_ref()) {
+                    if let Some((id, metadata)) = ctx.binaries.get(href.as_ref()) {
                         let alt =
                             text.into_iter()
                                 .map(|t| t.value)
@@ -613,8 +1030,12 @@ impl Span {
                                     a
                                 });
                         spans.push(Span::Image(InlineImage {
-                            id: id.clone(),
+                            id,
                             alt: non_empty(alt),
+                            width: metadata.width,
+                            height: metadata.height,
+                            mime_type: metadata.mime_type,
+                            placeholder: metadata.placeholder,
                         }));
                     } else if !text.is_empty() {
                         if ctx.notes.contains(href.as_ref()) {
@@ -681,55 +1102,52 @@ impl Span {
         spans
     }
 
-    fn from_fb2_link(
-        element: fb2::StyleLinkElement,
-        binary_ids: &HashMap<String, Uuid>,
-    ) -> Vec<Span> {
+    fn from_fb2_link(element: fb2::StyleLinkElement, binaries: &Binaries) -> Vec<Span> {
         let mut spans = vec![];
         match element {
             fb2::StyleLinkElement::Strong { elements } => spans.extend(
                 elements
                     .into_iter()
-                    .flat_map(|e| Span::from_fb2_link(e, binary_ids))
+                    .flat_map(|e| Span::from_fb2_link(e, binaries))
                     .map(bold_text),
             ),
             fb2::StyleLinkElement::Emphasis { elements } => spans.extend(
                 elements
                     .into_iter()
-                    .flat_map(|e| Span::from_fb2_link(e, binary_ids))
+                    .flat_map(|e| Span::from_fb2_link(e, binaries))
                     .map(italic_text),
             ),
             fb2::StyleLinkElement::Style { elements } => spans.extend(
                 elements
                     .into_iter()
-                    .flat_map(|e| Span::from_fb2_link(e, binary_ids)),
+                    .flat_map(|e| Span::from_fb2_link(e, binaries)),
             ),
             fb2::StyleLinkElement::Strikethrough { elements } => spans.extend(
                 elements
                     .into_iter()
-                    .flat_map(|e| Span::from_fb2_link(e, binary_ids))
+                    .flat_map(|e| Span::from_fb2_link(e, binaries))
                     .map(strikethrough_text),
             ),
             fb2::StyleLinkElement::Subscript { elements } => spans.extend(
                 elements
                     .into_iter()
-                    .flat_map(|e| Span::from_fb2_link(e, binary_ids))
+                    .flat_map(|e| Span::from_fb2_link(e, binaries))
                     .map(subscript_text),
             ),
             fb2::StyleLinkElement::Superscript { elements } => spans.extend(
                 elements
                     .into_iter()
-                    .flat_map(|e| Span::from_fb2_link(e, binary_ids))
+                    .flat_map(|e| Span::from_fb2_link(e, binaries))
                     .map(superscript_text),
             ),
             fb2::StyleLinkElement::Code { elements } => spans.extend(
                 elements
                     .into_iter()
-                    .flat_map(|e| Span::from_fb2_link(e, binary_ids))
+                    .flat_map(|e| Span::from_fb2_link(e, binaries))
                     .map(code_text),
             ),
             fb2::StyleLinkElement::Image(i) => {
-                if let Some(i) = InlineImage::from_fb2(i, binary_ids) {
+                if let Some(i) = InlineImage::from_fb2(i, binaries) {
                     spans.push(Span::Image(i));
                 }
             }
@@ -777,6 +1195,35 @@ fn strikethrough_text(span: Span) -> Span {
     })
 }
 
+// names that FB2 producers give to the styles missing in FB2
+fn named_style_text(span: Span, name: &str, style: Option<&Fb2Style>) -> Span {
+    hydrate_text(span, |text| match style {
+        Some(Fb2Style::Bold) => {
+            text.font_weight = Some(BOLD_WEIGHT);
+        }
+        Some(Fb2Style::FontStyle(s)) => {
+            text.font_style.insert(*s);
+        }
+        Some(Fb2Style::Decoration(d)) => {
+            text.decorations.insert(*d);
+        }
+        Some(Fb2Style::BaselineShift(b)) => {
+            text.baseline_shift = Some(*b);
+        }
+        None => {
+            text.classes.insert(name.to_string());
+        }
+    })
+}
+
+fn classes(style: Option<String>) -> HashSet<String> {
+    style
+        .iter()
+        .flat_map(|s| s.split_whitespace())
+        .map(|s| s.to_string())
+        .collect()
+}
+
 fn hydrate_text(mut span: Span, mut modifier: impl FnMut(&mut Text)) -> Span {
     match &mut span {
         Span::Footnote(f) => {
@@ -789,7 +1236,12 @@ fn hydrate_text(mut span: Span, mut modifier: impl FnMut(&mut Text)) -> Span {
                 modifier(text);
             }
         }
-        Span::Image(_) => {}
+        Span::Ruby(r) => {
+            for text in &mut r.base {
+                modifier(text);
+            }
+        }
+        Span::Image(_) | Span::Math(_) => {}
         Span::Text(t) => {
             modifier(t);
         }
@@ -798,18 +1250,16 @@ fn hydrate_text(mut span: Span, mut modifier: impl FnMut(&mut Text)) -> Span {
 }
 
 impl InlineImage {
-    fn from_fb2(
-        image: fb2::InlineImage,
-        binary_ids: &HashMap<String, Uuid>,
-    ) -> Option<InlineImage> {
-        image
-            .href
-            .and_then(non_empty)
-            .and_then(|href| href.strip_prefix('#').and_then(|href| binary_ids.get(href)))
-            .map(|id| InlineImage {
-                id: id.clone(),
-                alt: image.alt,
-            })
+    fn from_fb2(image: fb2::InlineImage, binaries: &Binaries) -> Option<InlineImage> {
+        let (id, metadata) = binaries.get_href(image.href)?;
+        Some(InlineImage {
+            id,
+            alt: image.alt,
+            width: metadata.width,
+            height: metadata.height,
+            mime_type: metadata.mime_type,
+            placeholder: metadata.placeholder,
+        })
     }
 }
 
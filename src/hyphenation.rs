@@ -0,0 +1,124 @@
+#[cfg(feature = "hyphenation")]
+use hypher::Lang;
+#[cfg(feature = "hyphenation")]
+use language_tags::LanguageTag;
+
+use crate::walk::for_each_spans_mut;
+#[cfg(feature = "hyphenation")]
+use crate::walk::for_each_spans_with_language_mut;
+use crate::{Book, Span};
+#[cfg(feature = "hyphenation")]
+use crate::{FontStyle, Href, Text};
+
+pub const SOFT_HYPHEN: char = '\u{00AD}';
+
+#[cfg(feature = "hyphenation")]
+impl Book {
+    /// Inserts soft hyphens into the words according to the language of the text,
+    /// which is inherited from the paragraph, cite, chapter or the book.
+    /// Code, URLs and the text of external links are left intact.
+    /// Returns `false` if no text is in a known and supported language.
+    pub fn hyphenate(&mut self) -> bool {
+        let mut hyphenated = false;
+        for_each_spans_with_language_mut(self, &mut |spans, language| {
+            let mut hyphenate_text = |text: &mut Text| {
+                let language = text.language.as_ref().or(language);
+                if let Some(language) = language.and_then(hyphenation_language) {
+                    hyphenate(text, language);
+                    hyphenated = true;
+                }
+            };
+            for span in spans {
+                match span {
+                    Span::Footnote(f) => f.content.iter_mut().for_each(&mut hyphenate_text),
+                    Span::Link(l) => {
+                        if let Href::Local(_) = l.href {
+                            l.content.iter_mut().for_each(&mut hyphenate_text);
+                        }
+                    }
+                    Span::Image(_) | Span::Math(_) | Span::Ruby(_) => {}
+                    Span::Text(t) => hyphenate_text(t),
+                }
+            }
+        });
+        hyphenated
+    }
+}
+
+impl Book {
+    pub fn remove_soft_hyphens(&mut self) {
+        for_each_spans_mut(self, &mut |spans| {
+            for span in spans {
+                let texts = match span {
+                    Span::Footnote(f) => &mut f.content[..],
+                    Span::Link(l) => &mut l.content[..],
+                    Span::Ruby(r) => &mut r.base[..],
+                    Span::Image(_) | Span::Math(_) => &mut [],
+                    Span::Text(t) => std::slice::from_mut(t),
+                };
+                for text in texts {
+                    if text.value.contains(SOFT_HYPHEN) {
+                        text.value.retain(|c| c != SOFT_HYPHEN);
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(feature = "hyphenation")]
+fn hyphenation_language(tag: &LanguageTag) -> Option<Lang> {
+    let code = tag.primary_language().to_ascii_lowercase();
+    let code: [u8; 2] = code.as_bytes().try_into().ok()?;
+    Lang::from_iso(code)
+}
+
+#[cfg(feature = "hyphenation")]
+fn hyphenate(text: &mut Text, language: Lang) {
+    if text.font_style.contains(&FontStyle::Code) {
+        return;
+    }
+    let mut hyphenated = String::with_capacity(text.value.len());
+    for token in text.value.split_inclusive(char::is_whitespace) {
+        if is_url(token) || token.contains(SOFT_HYPHEN) {
+            hyphenated.push_str(token);
+            continue;
+        }
+        let mut word_start = None;
+        for (i, c) in token.char_indices() {
+            if is_word_char(c) {
+                word_start.get_or_insert(i);
+            } else if let Some(start) = word_start.take() {
+                push_hyphenated(&mut hyphenated, &token[start..i], language);
+                hyphenated.push(c);
+            } else {
+                hyphenated.push(c);
+            }
+        }
+        if let Some(start) = word_start {
+            push_hyphenated(&mut hyphenated, &token[start..], language);
+        }
+    }
+    text.value = hyphenated;
+}
+
+#[cfg(feature = "hyphenation")]
+fn push_hyphenated(target: &mut String, word: &str, language: Lang) {
+    for (i, syllable) in hypher::hyphenate(word, language).enumerate() {
+        if i > 0 {
+            target.push(SOFT_HYPHEN);
+        }
+        target.push_str(syllable);
+    }
+}
+
+// letters and combining marks, such as stress marks in textbooks
+#[cfg(feature = "hyphenation")]
+fn is_word_char(c: char) -> bool {
+    c.is_alphabetic() || matches!(c, '\u{0300}'..='\u{036F}')
+}
+
+pub(crate) fn is_url(token: &str) -> bool {
+    let token = token.trim();
+    token.contains("://") || token.starts_with("www.") || token.contains('@')
+}
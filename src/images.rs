@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::imageops::FilterType;
+use image::DynamicImage;
+use uuid::Uuid;
+
+use crate::placeholder::dominant_color;
+use crate::walk::for_each_image_mut;
+use crate::{Book, Placeholder, ResourceVariant};
+
+/// Kind of screen the images are prepared for
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageProfile {
+    /// Larger images are downscaled to fit, keeping the aspect ratio
+    pub max_width: u32,
+    pub max_height: u32,
+    pub format: VariantFormat,
+    /// Shades of gray, for e-ink screens
+    pub grayscale: bool,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum VariantFormat {
+    /// Quality from 1 to 100
+    Jpeg { quality: u8 },
+    /// Lossless WebP
+    WebP,
+}
+
+impl Book {
+    /// Encodes a variant of every image in the resources for each profile and records the
+    /// variants in [`Book::resources`], replacing the previous ones. Returns the data of
+    /// the variants by their ids. Resources that cannot be decoded as images are skipped.
+    /// Metadata of the source, such as EXIF or color profiles, is not carried over.
+    /// Images without a placeholder get the dominant color of the source.
+    pub fn optimize_images(
+        &mut self,
+        resources: &HashMap<Uuid, Vec<u8>>,
+        profiles: &[ImageProfile],
+    ) -> HashMap<Uuid, Vec<u8>> {
+        let mut data = HashMap::new();
+        let mut colors = HashMap::new();
+        for (id, source) in resources {
+            let Ok(image) = image::load_from_memory(source) else {
+                continue;
+            };
+            if let Some(color) = dominant_color(&image) {
+                colors.insert(*id, color);
+            }
+            let mut variants = vec![];
+            for profile in profiles {
+                let Some((variant, bytes)) = encode(&image, profile) else {
+                    continue;
+                };
+                data.insert(variant.id, bytes);
+                variants.push(variant);
+            }
+            self.resources.entry(*id).or_default().variants = variants;
+        }
+        for_each_image_mut(self, &mut |id, placeholder| {
+            if placeholder.is_none() {
+                *placeholder = colors.get(id).cloned().map(Placeholder::Color);
+            }
+        });
+        data
+    }
+}
+
+fn encode(image: &DynamicImage, profile: &ImageProfile) -> Option<(ResourceVariant, Vec<u8>)> {
+    let image = if image.width() > profile.max_width || image.height() > profile.max_height {
+        image.resize(profile.max_width, profile.max_height, FilterType::Lanczos3)
+    } else {
+        image.clone()
+    };
+    // the encoders take 8-bit channels only, and JPEG has no transparency
+    let image = if profile.grayscale {
+        DynamicImage::ImageLuma8(image.to_luma8())
+    } else if image.color().has_alpha() && profile.format == VariantFormat::WebP {
+        DynamicImage::ImageRgba8(image.to_rgba8())
+    } else {
+        DynamicImage::ImageRgb8(image.to_rgb8())
+    };
+    let mut bytes = Cursor::new(vec![]);
+    let mime_type = match profile.format {
+        VariantFormat::Jpeg { quality } => {
+            let encoder = JpegEncoder::new_with_quality(&mut bytes, quality.clamp(1, 100));
+            image.write_with_encoder(encoder).ok()?;
+            "image/jpeg"
+        }
+        VariantFormat::WebP => {
+            image
+                .write_with_encoder(WebPEncoder::new_lossless(&mut bytes))
+                .ok()?;
+            "image/webp"
+        }
+    };
+    let variant = ResourceVariant {
+        id: Uuid::new_v4(),
+        mime_type: mime_type.to_string(),
+        width: image.width(),
+        height: image.height(),
+        grayscale: profile.grayscale,
+    };
+    Some((variant, bytes.into_inner()))
+}
@@ -0,0 +1,95 @@
+use std::fmt;
+
+use crate::Isbn;
+
+impl Isbn {
+    /// Parses an ISBN-10 or ISBN-13, possibly with hyphens, spaces and an "ISBN" prefix.
+    /// Returns `None` if the check digit does not match.
+    pub fn parse(value: &str) -> Option<Isbn> {
+        let value = value.trim();
+        let value = match value.get(..4) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("isbn") => &value[4..],
+            _ => value,
+        };
+        let value = value
+            .strip_prefix("-13")
+            .or_else(|| value.strip_prefix("-10"))
+            .unwrap_or(value)
+            .trim_start_matches([':', ' ']);
+        let digits: String = value
+            .chars()
+            .filter(|c| !matches!(c, '-' | ' ' | '\u{2010}'..='\u{2015}'))
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        let valid = match digits.len() {
+            10 => is_valid_isbn10(&digits),
+            13 => is_valid_isbn13(&digits),
+            _ => false,
+        };
+        valid.then_some(Isbn(digits))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The same ISBN in 13 digits, with the 978 prefix for ISBN-10
+    pub fn to_isbn13(&self) -> Isbn {
+        if self.0.len() == 13 {
+            return self.clone();
+        }
+        let mut digits = format!("978{}", &self.0[..9]);
+        let sum = weighted_isbn13_sum(&digits);
+        digits.push(char::from_digit((10 - sum % 10) % 10, 10).unwrap());
+        Isbn(digits)
+    }
+}
+
+fn is_valid_isbn10(digits: &str) -> bool {
+    let mut sum = 0;
+    for (i, c) in digits.chars().enumerate() {
+        let value = match c {
+            'X' if i == 9 => 10,
+            c => match c.to_digit(10) {
+                Some(d) => d,
+                None => return false,
+            },
+        };
+        sum += (10 - i as u32) * value;
+    }
+    sum.is_multiple_of(11)
+}
+
+fn is_valid_isbn13(digits: &str) -> bool {
+    digits.chars().all(|c| c.is_ascii_digit()) && weighted_isbn13_sum(digits).is_multiple_of(10)
+}
+
+// digits weighted 1 and 3 in turn
+fn weighted_isbn13_sum(digits: &str) -> u32 {
+    digits
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, d)| if i % 2 == 0 { d } else { d * 3 })
+        .sum()
+}
+
+impl TryFrom<String> for Isbn {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Isbn::parse(&value).ok_or_else(|| format!("invalid ISBN: {value}"))
+    }
+}
+
+impl From<Isbn> for String {
+    fn from(value: Isbn) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for Isbn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
@@ -0,0 +1,76 @@
+use language_tags::LanguageTag;
+
+use crate::walk::{parts, ruby_fallback, Block, BlockKind};
+use crate::{Book, Direction, Position, Span, Text};
+
+/// Primary languages written from right to left
+const RTL_LANGUAGES: [&str; 8] = ["ar", "dv", "fa", "he", "ps", "syr", "ur", "yi"];
+
+impl Book {
+    /// Language of the text at the position: of the text itself, or of the closest paragraph,
+    /// cite or chapter around it, or of the book
+    pub fn effective_language(&self, position: &Position) -> Option<&LanguageTag> {
+        let (block, text) = self.locate(position)?;
+        text.and_then(|t| t.language.as_ref()).or(block.language)
+    }
+
+    /// Direction of the text at the position, resolved like [`Book::effective_language`].
+    /// Without an explicit direction it follows the script of the language.
+    pub fn effective_direction(&self, position: &Position) -> Option<Direction> {
+        let (block, text) = self.locate(position)?;
+        let direction = text.and_then(|t| t.direction).or(block.direction);
+        let language = text.and_then(|t| t.language.as_ref()).or(block.language);
+        Some(direction.unwrap_or_else(|| direction_of(language)))
+    }
+
+    fn locate(&self, position: &Position) -> Option<(Block<'_>, Option<&Text>)> {
+        let part = parts(self).into_iter().find(|p| p.part == position.part)?;
+        let block = part.blocks.into_iter().nth(position.block)?;
+        let text = match block.kind {
+            BlockKind::Text(spans) => text_at(spans, position.offset),
+            _ => None,
+        };
+        Some((block, text))
+    }
+}
+
+fn direction_of(language: Option<&LanguageTag>) -> Direction {
+    let rtl = language.is_some_and(|l| {
+        let primary = l.primary_language().to_ascii_lowercase();
+        RTL_LANGUAGES.contains(&primary.as_str())
+    });
+    if rtl {
+        Direction::Rtl
+    } else {
+        Direction::Ltr
+    }
+}
+
+// the text with the character at the offset in the plain text of the spans
+fn text_at(spans: &[Span], offset: usize) -> Option<&Text> {
+    let mut start = 0;
+    for span in spans {
+        let texts = match span {
+            Span::Footnote(f) => &f.content[..],
+            Span::Link(l) => &l.content[..],
+            Span::Image(_) => &[],
+            Span::Ruby(r) => &r.base[..],
+            Span::Math(m) => {
+                start += m.alt.chars().count();
+                continue;
+            }
+            Span::Text(t) => std::slice::from_ref(t),
+        };
+        for text in texts {
+            let end = start + text.value.chars().count();
+            if (start..end).contains(&offset) {
+                return Some(text);
+            }
+            start = end;
+        }
+        if let Span::Ruby(r) = span {
+            start += ruby_fallback(&r.annotation).chars().count();
+        }
+    }
+    None
+}
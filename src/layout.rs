@@ -0,0 +1,907 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::walk::{parts, ruby_fallback, Block, BlockKind, Role};
+use crate::{
+    BaselineShift, Book, FontStyle, HorizontalAlign, Part, Position, Span, Text, VerticalAlign,
+    SOFT_HYPHEN,
+};
+
+/// Measures text for the layout, e.g. with the fonts of the reader
+pub trait TextMeasurer {
+    /// Horizontal advance of the character
+    fn advance(&self, c: char, style: &TextStyle) -> f32;
+    fn line_height(&self, style: &TextStyle) -> f32;
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub struct TextStyle {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font_weight: Option<u16>,
+    pub italic: bool,
+    pub monospace: bool,
+    pub small_caps: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub baseline_shift: Option<BaselineShift>,
+    /// Titles and subtitles
+    pub heading: bool,
+}
+
+/// Every character has the same advance, handy for terminals and tests
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct MonospaceMeasurer {
+    pub advance: f32,
+    pub line_height: f32,
+}
+
+impl TextMeasurer for MonospaceMeasurer {
+    fn advance(&self, c: char, _style: &TextStyle) -> f32 {
+        if is_zero_width(c) {
+            0.0
+        } else {
+            self.advance
+        }
+    }
+
+    fn line_height(&self, _style: &TextStyle) -> f32 {
+        self.line_height
+    }
+}
+
+fn is_zero_width(c: char) -> bool {
+    matches!(c, SOFT_HYPHEN | '\u{200B}'..='\u{200D}' | '\u{FEFF}' | '\u{0300}'..='\u{036F}')
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Viewport {
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayoutOptions {
+    pub viewport: Viewport,
+    pub paragraph_spacing: f32,
+    pub first_line_indent: f32,
+    /// Indentation of annotations, epigraphs and cites per nesting level
+    pub nested_indent: f32,
+    /// Minimum number of lines of a paragraph left at the bottom of a page
+    pub orphans: usize,
+    /// Minimum number of lines of a paragraph carried over to the next page
+    pub widows: usize,
+    /// Height of images as a fraction of the viewport height
+    pub image_height: f32,
+    /// Shown centered in place of scene breaks
+    pub scene_break: String,
+}
+
+impl LayoutOptions {
+    pub fn new(viewport: Viewport) -> LayoutOptions {
+        LayoutOptions {
+            viewport,
+            paragraph_spacing: 0.0,
+            first_line_indent: 0.0,
+            nested_indent: 0.0,
+            orphans: 2,
+            widows: 2,
+            image_height: 0.5,
+            scene_break: "* * *".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Page {
+    /// Position of the first character on the page
+    pub start: Position,
+    /// Position right after the last character on the page
+    pub end: Position,
+    pub runs: Vec<Run>,
+}
+
+/// Content placed on a page, coordinates are relative to the top left corner of the viewport
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Run {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub position: Position,
+    pub content: RunContent,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub enum RunContent {
+    Text { text: String, style: TextStyle },
+    Image(Uuid),
+}
+
+/// Pages of the book title, epigraphs and chapters, every chapter starts on a new page
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layout {
+    pages: Vec<Page>,
+    anchors: HashMap<String, Position>,
+    // number of blocks in every laid out part
+    blocks: HashMap<Part, usize>,
+}
+
+impl Layout {
+    pub fn new(book: &Book, measurer: &impl TextMeasurer, options: &LayoutOptions) -> Layout {
+        let mut paginator = Paginator {
+            options,
+            pages: vec![],
+            page: None,
+            y: 0.0,
+        };
+        let mut anchors = HashMap::new();
+        let mut blocks = HashMap::new();
+        for part in parts(book) {
+            if !matches!(part.part, Part::Front | Part::Chapter(_)) {
+                continue;
+            }
+            if let Some(anchor) = part.anchor {
+                anchors.insert(anchor.to_string(), Position::start_of(part.part.clone()));
+            }
+            for (i, block) in part.blocks.iter().enumerate() {
+                if let Some(anchor) = block.anchor {
+                    anchors.insert(anchor.to_string(), Position::new(part.part.clone(), i, 0));
+                }
+            }
+            blocks.insert(part.part.clone(), part.blocks.len());
+            let chunks = Typesetter {
+                measurer,
+                options,
+                part: &part.part,
+            }
+            .chunks(&part.blocks);
+            paginator.finish_page();
+            paginator.place(&part.part, chunks);
+        }
+        paginator.finish_page();
+        Layout {
+            pages: paginator.pages,
+            anchors,
+            blocks,
+        }
+    }
+
+    pub fn pages(&self) -> &[Page] {
+        &self.pages
+    }
+
+    /// Index of the page showing the position, `None` if the position is not laid out
+    pub fn page_of(&self, position: &Position) -> Option<usize> {
+        let blocks = self.blocks.get(&position.part)?;
+        if position.block >= *blocks || self.pages.is_empty() {
+            return None;
+        }
+        let next = self.pages.partition_point(|p| p.start <= *position);
+        Some(next.saturating_sub(1))
+    }
+
+    pub fn page_of_anchor(&self, anchor: &str) -> Option<usize> {
+        self.anchors.get(anchor).and_then(|p| self.page_of(p))
+    }
+}
+
+// a line of text, an image or a table row, which is never split between pages
+struct Line {
+    height: f32,
+    // y is relative to the top of the line
+    runs: Vec<Run>,
+    start: (usize, usize),
+    end: (usize, usize),
+}
+
+// lines of a single block, which are kept together according to widows and orphans
+struct Chunk {
+    lines: Vec<Line>,
+    spacing: f32,
+    breakable: bool,
+    keep_with_next: bool,
+}
+
+impl Chunk {
+    fn height(&self) -> f32 {
+        self.lines.iter().map(|l| l.height).sum()
+    }
+}
+
+struct Typesetter<'a, M> {
+    measurer: &'a M,
+    options: &'a LayoutOptions,
+    part: &'a Part,
+}
+
+#[derive(Copy, Clone)]
+enum Item {
+    Char(char, TextStyle),
+    Image(Uuid, TextStyle),
+}
+
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+impl<M: TextMeasurer> Typesetter<'_, M> {
+    fn chunks(&self, blocks: &[Block]) -> Vec<Chunk> {
+        let mut chunks = vec![];
+        let mut i = 0;
+        while i < blocks.len() {
+            let block = &blocks[i];
+            if let Role::TableCell { .. } = block.role {
+                let mut end = i + 1;
+                while end < blocks.len() {
+                    match blocks[end].role {
+                        Role::TableCell {
+                            row: 0, column: 0, ..
+                        } => break,
+                        Role::TableCell { .. } => end += 1,
+                        _ => break,
+                    }
+                }
+                chunks.extend(self.table(&blocks[i..end], i));
+                i = end;
+                continue;
+            }
+            chunks.push(self.block(block, i));
+            i += 1;
+        }
+        chunks
+    }
+
+    fn block(&self, block: &Block, index: usize) -> Chunk {
+        let spacing = self.options.paragraph_spacing;
+        let x = block.indent as f32 * self.options.nested_indent;
+        let width = self.options.viewport.width - x;
+        match block.kind {
+            BlockKind::EmptyLine => Chunk {
+                lines: vec![Line {
+                    height: self.measurer.line_height(&TextStyle::default()),
+                    runs: vec![],
+                    start: (index, 0),
+                    end: (index, 0),
+                }],
+                spacing: 0.0,
+                breakable: false,
+                keep_with_next: false,
+            },
+            BlockKind::SceneBreak => {
+                let style = TextStyle::default();
+                let text = self.options.scene_break.clone();
+                let text_width = text
+                    .chars()
+                    .map(|c| self.measurer.advance(c, &style))
+                    .sum::<f32>();
+                let height = self.measurer.line_height(&style);
+                Chunk {
+                    lines: vec![Line {
+                        height,
+                        runs: vec![Run {
+                            x: x + ((width - text_width) / 2.0).max(0.0),
+                            y: 0.0,
+                            width: text_width,
+                            height,
+                            position: self.position(index, 0),
+                            content: RunContent::Text { text, style },
+                        }],
+                        start: (index, 0),
+                        end: (index, 0),
+                    }],
+                    spacing,
+                    breakable: false,
+                    keep_with_next: false,
+                }
+            }
+            BlockKind::Image(image) => {
+                let viewport = self.options.viewport;
+                let mut height = viewport.height * self.options.image_height.clamp(0.0, 1.0);
+                let (mut x, mut width) = (x, width);
+                if let (Some(w), Some(h)) = (image.width, image.height) {
+                    if w > 0 && h > 0 {
+                        // keep the aspect ratio within the reserved box
+                        let scale = (width / w as f32).min(height / h as f32);
+                        x += (width - w as f32 * scale) / 2.0;
+                        width = w as f32 * scale;
+                        height = h as f32 * scale;
+                    }
+                }
+                Chunk {
+                    lines: vec![Line {
+                        height,
+                        runs: vec![Run {
+                            x,
+                            y: 0.0,
+                            width,
+                            height,
+                            position: self.position(index, 0),
+                            content: RunContent::Image(image.id),
+                        }],
+                        start: (index, 0),
+                        end: (index, 0),
+                    }],
+                    spacing,
+                    breakable: false,
+                    keep_with_next: !image.caption.is_empty(),
+                }
+            }
+            BlockKind::Verbatim(line) => {
+                let style = TextStyle {
+                    monospace: true,
+                    ..TextStyle::default()
+                };
+                let items = line
+                    .chars()
+                    .enumerate()
+                    .map(|(offset, c)| (offset, Item::Char(c, style)))
+                    .collect::<Vec<_>>();
+                Chunk {
+                    lines: self.lines(&items, index, x, width, (0.0, 0.0), Align::Left),
+                    spacing: match block.role {
+                        Role::Preformatted { first: false } => 0.0,
+                        _ => spacing,
+                    },
+                    breakable: true,
+                    keep_with_next: false,
+                }
+            }
+            BlockKind::Math(math) => {
+                let mut items = vec![];
+                push_math(&mut items, &math.alt, false);
+                Chunk {
+                    lines: self.lines(&items, index, x, width, (0.0, 0.0), Align::Center),
+                    spacing,
+                    breakable: true,
+                    keep_with_next: false,
+                }
+            }
+            BlockKind::Text(spans) => {
+                let heading = matches!(block.role, Role::Title | Role::Subtitle);
+                let (first_indent, hanging_indent) = match block.role {
+                    Role::Paragraph => (self.options.first_line_indent, 0.0),
+                    Role::Verse { .. } => (0.0, self.options.first_line_indent),
+                    _ => (0.0, 0.0),
+                };
+                let align = match block.role {
+                    Role::Title | Role::Subtitle | Role::Caption => Align::Center,
+                    Role::TextAuthor => Align::Right,
+                    _ => Align::Left,
+                };
+                let spacing = match block.role {
+                    Role::Verse { first: false, .. } => 0.0,
+                    _ => spacing,
+                };
+                let mut lines = self.lines(
+                    &items(spans, heading),
+                    index,
+                    x,
+                    width,
+                    (first_indent, hanging_indent),
+                    align,
+                );
+                if let (Role::ListItem { number }, Some(line)) = (block.role, lines.first_mut()) {
+                    line.runs
+                        .insert(0, self.marker(number, index, x, line.height));
+                }
+                Chunk {
+                    lines,
+                    spacing,
+                    breakable: true,
+                    keep_with_next: matches!(block.role, Role::Title | Role::Caption),
+                }
+            }
+        }
+    }
+
+    fn table(&self, cells: &[Block], first: usize) -> Vec<Chunk> {
+        let columns = cells
+            .iter()
+            .filter_map(|c| match c.role {
+                Role::TableCell {
+                    column,
+                    column_span,
+                    ..
+                } => Some(column + column_span),
+                _ => None,
+            })
+            .max()
+            .unwrap_or(1);
+        let indent = cells.first().map(|c| c.indent).unwrap_or_default();
+        let x = indent as f32 * self.options.nested_indent;
+        let column_width = (self.options.viewport.width - x) / columns as f32;
+
+        let mut rows: Vec<Vec<(usize, &Block)>> = vec![];
+        let mut last_row = None;
+        for (i, cell) in cells.iter().enumerate() {
+            let row = match cell.role {
+                Role::TableCell { row, .. } => Some(row),
+                _ => None,
+            };
+            if rows.is_empty() || row != last_row {
+                rows.push(vec![]);
+                last_row = row;
+            }
+            if let Some(row) = rows.last_mut() {
+                row.push((first + i, cell));
+            }
+        }
+
+        let mut chunks = vec![];
+        for (r, row) in rows.into_iter().enumerate() {
+            let mut height: f32 = 0.0;
+            let mut cell_runs = vec![];
+            for (index, cell) in &row {
+                // cells spanning several rows are set in their first row
+                let Role::TableCell {
+                    column,
+                    column_span,
+                    header,
+                    horizontal_align,
+                    vertical_align,
+                    ..
+                } = cell.role
+                else {
+                    continue;
+                };
+                let BlockKind::Text(spans) = cell.kind else {
+                    continue;
+                };
+                let align = match horizontal_align {
+                    None | Some(HorizontalAlign::Left) => Align::Left,
+                    Some(HorizontalAlign::Center) => Align::Center,
+                    Some(HorizontalAlign::Right) => Align::Right,
+                };
+                let cell_x = x + column as f32 * column_width;
+                let lines = self.lines(
+                    &items(spans, header),
+                    *index,
+                    cell_x,
+                    column_width * column_span as f32,
+                    (0.0, 0.0),
+                    align,
+                );
+                let mut runs = vec![];
+                let mut y = 0.0;
+                for line in lines {
+                    runs.extend(line.runs.into_iter().map(|mut run| {
+                        run.y += y;
+                        run
+                    }));
+                    y += line.height;
+                }
+                height = height.max(y);
+                cell_runs.push((runs, y, vertical_align));
+            }
+            let mut runs = vec![];
+            for (cell, cell_height, vertical_align) in cell_runs {
+                let shift = match vertical_align {
+                    None | Some(VerticalAlign::Top) => 0.0,
+                    Some(VerticalAlign::Middle) => (height - cell_height) / 2.0,
+                    Some(VerticalAlign::Bottom) => height - cell_height,
+                };
+                runs.extend(cell.into_iter().map(|mut run| {
+                    run.y += shift;
+                    run
+                }));
+            }
+            let start = row.first().map(|(i, _)| (*i, 0)).unwrap_or((first, 0));
+            let end = row
+                .last()
+                .map(|(i, cell)| (*i, cell.text().chars().count()))
+                .unwrap_or(start);
+            chunks.push(Chunk {
+                lines: vec![Line {
+                    height,
+                    runs,
+                    start,
+                    end,
+                }],
+                spacing: if r == 0 {
+                    self.options.paragraph_spacing
+                } else {
+                    0.0
+                },
+                breakable: false,
+                keep_with_next: false,
+            });
+        }
+        chunks
+    }
+
+    fn lines(
+        &self,
+        items: &[(usize, Item)],
+        index: usize,
+        x: f32,
+        width: f32,
+        (first_indent, hanging_indent): (f32, f32),
+        align: Align,
+    ) -> Vec<Line> {
+        let total = items
+            .iter()
+            .filter(|(_, i)| matches!(i, Item::Char(..)))
+            .count();
+        let offset_at = |i: usize| items.get(i).map(|(o, _)| *o).unwrap_or(total);
+        let mut lines = vec![];
+        let mut i = 0;
+        loop {
+            let indent = if lines.is_empty() {
+                first_indent
+            } else {
+                hanging_indent
+            };
+            let max = width - indent;
+            let mut line_width = 0.0;
+            let mut j = i;
+            // end of the line before a space, and start of the next line after it
+            let mut last_break = None;
+            let mut end = items.len();
+            let mut next = items.len();
+            while j < items.len() {
+                let (_, item) = items[j];
+                if is_space(&item) {
+                    let space_start = j;
+                    while j < items.len() && is_space(&items[j].1) {
+                        line_width += self.advance(&items[j].1);
+                        j += 1;
+                    }
+                    last_break = Some((space_start, j));
+                    continue;
+                }
+                let advance = self.advance(&item);
+                if line_width + advance > max && j > i {
+                    (end, next) = last_break.unwrap_or((j, j));
+                    break;
+                }
+                if let Item::Char(SOFT_HYPHEN, style) = item {
+                    if line_width + self.measurer.advance('-', &style) <= max {
+                        last_break = Some((j + 1, j + 1));
+                    }
+                }
+                line_width += advance;
+                j += 1;
+            }
+            let mut end_trimmed = end;
+            while end_trimmed > i && is_space(&items[end_trimmed - 1].1) {
+                end_trimmed -= 1;
+            }
+            let (mut runs, height) = self.runs(&items[i..end_trimmed], index, x + indent);
+            let content_width = runs.iter().map(|r| r.width).sum::<f32>();
+            let shift = match align {
+                Align::Left => 0.0,
+                Align::Center => ((max - content_width) / 2.0).max(0.0),
+                Align::Right => (max - content_width).max(0.0),
+            };
+            runs.iter_mut().for_each(|r| r.x += shift);
+            lines.push(Line {
+                height,
+                runs,
+                start: (index, offset_at(i)),
+                end: (index, offset_at(next)),
+            });
+            if next >= items.len() {
+                break;
+            }
+            i = next;
+        }
+        lines
+    }
+
+    fn runs(&self, items: &[(usize, Item)], index: usize, x: f32) -> (Vec<Run>, f32) {
+        let mut runs: Vec<Run> = vec![];
+        let mut height: f32 = 0.0;
+        let mut x = x;
+        for (k, (offset, item)) in items.iter().enumerate() {
+            // soft hyphens are only visible at the end of a line
+            let item = &match item {
+                Item::Char(SOFT_HYPHEN, style) if k + 1 == items.len() => Item::Char('-', *style),
+                Item::Char(SOFT_HYPHEN, _) => continue,
+                item => *item,
+            };
+            let advance = self.advance(item);
+            match item {
+                Item::Char(c, style) => {
+                    height = height.max(self.measurer.line_height(style));
+                    if let Some(Run {
+                        width,
+                        content: RunContent::Text { text, style: last },
+                        ..
+                    }) = runs.last_mut()
+                    {
+                        if last == style {
+                            text.push(*c);
+                            *width += advance;
+                            x += advance;
+                            continue;
+                        }
+                    }
+                    runs.push(Run {
+                        x,
+                        y: 0.0,
+                        width: advance,
+                        height: 0.0,
+                        position: self.position(index, *offset),
+                        content: RunContent::Text {
+                            text: c.to_string(),
+                            style: *style,
+                        },
+                    });
+                }
+                Item::Image(id, style) => {
+                    height = height.max(self.measurer.line_height(style));
+                    runs.push(Run {
+                        x,
+                        y: 0.0,
+                        width: advance,
+                        height: advance,
+                        position: self.position(index, *offset),
+                        content: RunContent::Image(*id),
+                    });
+                }
+            }
+            x += advance;
+        }
+        if height == 0.0 {
+            height = self.measurer.line_height(&TextStyle::default());
+        }
+        for run in &mut runs {
+            if let RunContent::Text { .. } = run.content {
+                run.height = height;
+            }
+        }
+        (runs, height)
+    }
+
+    // the number or the bullet of a list item, in the indent of the list
+    fn marker(&self, number: Option<u32>, index: usize, x: f32, height: f32) -> Run {
+        let text = number.map_or("•".to_string(), |n| format!("{n}."));
+        let style = TextStyle::default();
+        let width = text.chars().map(|c| self.measurer.advance(c, &style)).sum();
+        Run {
+            x: (x - self.options.nested_indent).max(0.0),
+            y: 0.0,
+            width,
+            height,
+            position: self.position(index, 0),
+            content: RunContent::Text { text, style },
+        }
+    }
+
+    fn advance(&self, item: &Item) -> f32 {
+        match item {
+            Item::Char(c, style) => self.measurer.advance(*c, style),
+            Item::Image(_, style) => self.measurer.line_height(style),
+        }
+    }
+
+    fn position(&self, block: usize, offset: usize) -> Position {
+        Position::new(self.part.clone(), block, offset)
+    }
+}
+
+fn is_space(item: &Item) -> bool {
+    matches!(item, Item::Char(c, _) if c.is_whitespace() && *c != '\u{00A0}')
+}
+
+// characters and inline images with the offset of the character in the plain text of the block
+fn items(spans: &[Span], heading: bool) -> Vec<(usize, Item)> {
+    let mut items = vec![];
+    for span in spans {
+        match span {
+            Span::Footnote(f) => f
+                .content
+                .iter()
+                .for_each(|t| push_text(&mut items, t, heading)),
+            Span::Link(l) => l
+                .content
+                .iter()
+                .for_each(|t| push_text(&mut items, t, heading)),
+            Span::Image(i) => {
+                let style = TextStyle {
+                    heading,
+                    ..TextStyle::default()
+                };
+                items.push((next_offset(&items), Item::Image(i.id, style)))
+            }
+            Span::Math(m) => push_math(&mut items, &m.alt, heading),
+            Span::Ruby(r) => {
+                r.base
+                    .iter()
+                    .for_each(|t| push_text(&mut items, t, heading));
+                let style = r
+                    .base
+                    .last()
+                    .map(|t| text_style(t, heading))
+                    .unwrap_or(TextStyle {
+                        heading,
+                        ..TextStyle::default()
+                    });
+                push_chars(&mut items, &ruby_fallback(&r.annotation), style);
+            }
+            Span::Text(t) => push_text(&mut items, t, heading),
+        }
+    }
+    items
+}
+
+fn push_text(items: &mut Vec<(usize, Item)>, text: &Text, heading: bool) {
+    push_chars(items, &text.value, text_style(text, heading));
+}
+
+// formulas are set as their alt text, since there is no math renderer
+fn push_math(items: &mut Vec<(usize, Item)>, alt: &str, heading: bool) {
+    let style = TextStyle {
+        italic: true,
+        heading,
+        ..TextStyle::default()
+    };
+    push_chars(items, alt, style);
+}
+
+fn push_chars(items: &mut Vec<(usize, Item)>, text: &str, style: TextStyle) {
+    for (offset, c) in (next_offset(items)..).zip(text.chars()) {
+        items.push((offset, Item::Char(c, style)));
+    }
+}
+
+fn next_offset(items: &[(usize, Item)]) -> usize {
+    match items.last() {
+        Some((offset, Item::Char(..))) => offset + 1,
+        Some((offset, Item::Image(..))) => *offset,
+        None => 0,
+    }
+}
+
+fn text_style(text: &Text, heading: bool) -> TextStyle {
+    TextStyle {
+        font_weight: text.font_weight,
+        italic: text.font_style.contains(&FontStyle::Italic),
+        monospace: text.font_style.contains(&FontStyle::Code),
+        small_caps: text.font_style.contains(&FontStyle::SmallCaps),
+        baseline_shift: text.baseline_shift,
+        heading,
+    }
+}
+
+struct Paginator<'a> {
+    options: &'a LayoutOptions,
+    pages: Vec<Page>,
+    page: Option<Page>,
+    y: f32,
+}
+
+impl Paginator<'_> {
+    fn place(&mut self, part: &Part, chunks: Vec<Chunk>) {
+        let height = self.options.viewport.height;
+        let lead = self.lead_heights(&chunks);
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            let spacing = if self.y == 0.0 { 0.0 } else { chunk.spacing };
+            let chunk_height = chunk.height();
+            let mut needed = spacing + chunk_height;
+            if chunk.keep_with_next {
+                needed += lead.get(i + 1).copied().unwrap_or_default();
+            }
+            if self.y + needed <= height {
+                self.y += spacing;
+                chunk
+                    .lines
+                    .into_iter()
+                    .for_each(|l| self.place_line(part, l));
+                continue;
+            }
+            if !chunk.breakable || chunk.keep_with_next {
+                if self.y > 0.0 {
+                    self.finish_page();
+                }
+                if !chunk.breakable || chunk_height <= height {
+                    chunk
+                        .lines
+                        .into_iter()
+                        .for_each(|l| self.place_line(part, l));
+                    continue;
+                }
+                // taller than a page, keeping it with the next chunk is not possible
+                self.split(part, chunk.lines, 0.0);
+                continue;
+            }
+            self.split(part, chunk.lines, spacing);
+        }
+    }
+
+    // height needed at the top of each chunk to satisfy orphans and keep-with-next
+    fn lead_heights(&self, chunks: &[Chunk]) -> Vec<f32> {
+        let mut lead = vec![0.0; chunks.len()];
+        for i in (0..chunks.len()).rev() {
+            let chunk = &chunks[i];
+            lead[i] = if chunk.keep_with_next {
+                let next = chunks
+                    .get(i + 1)
+                    .map(|n| n.spacing + lead[i + 1])
+                    .unwrap_or_default();
+                chunk.spacing + chunk.height() + next
+            } else if chunk.breakable {
+                let orphans = self.options.orphans.max(1).min(chunk.lines.len());
+                chunk.spacing + chunk.lines[..orphans].iter().map(|l| l.height).sum::<f32>()
+            } else {
+                chunk.spacing + chunk.height()
+            };
+        }
+        lead
+    }
+
+    fn split(&mut self, part: &Part, lines: Vec<Line>, spacing: f32) {
+        let height = self.options.viewport.height;
+        let mut lines = lines.into_iter();
+        let mut spacing = spacing;
+        while lines.len() > 0 {
+            let remaining = lines.len();
+            let available = height - self.y - spacing;
+            let mut fit = 0;
+            let mut used = 0.0;
+            for line in lines.as_slice() {
+                if used + line.height > available {
+                    break;
+                }
+                used += line.height;
+                fit += 1;
+            }
+            let mut take = fit;
+            if take < remaining {
+                if take < self.options.orphans.min(remaining) {
+                    take = 0;
+                }
+                if remaining - take < self.options.widows {
+                    take = remaining.saturating_sub(self.options.widows);
+                    if take < self.options.orphans {
+                        take = 0;
+                    }
+                }
+            }
+            if take == 0 {
+                if self.y == 0.0 {
+                    // not even a single line fits the page, keep going anyway
+                    take = fit.max(1);
+                } else {
+                    self.finish_page();
+                    spacing = 0.0;
+                    continue;
+                }
+            }
+            self.y += spacing;
+            for line in lines.by_ref().take(take) {
+                self.place_line(part, line);
+            }
+            if lines.len() > 0 {
+                self.finish_page();
+                spacing = 0.0;
+            }
+        }
+    }
+
+    fn place_line(&mut self, part: &Part, line: Line) {
+        let y = self.y;
+        let end = Position::new(part.clone(), line.end.0, line.end.1);
+        let page = self.page.get_or_insert_with(|| Page {
+            start: Position::new(part.clone(), line.start.0, line.start.1),
+            end: end.clone(),
+            runs: vec![],
+        });
+        page.end = end;
+        page.runs.extend(line.runs.into_iter().map(|mut run| {
+            run.y += y;
+            run
+        }));
+        self.y += line.height;
+    }
+
+    fn finish_page(&mut self) {
+        if let Some(page) = self.page.take() {
+            self.pages.push(page);
+        }
+        self.y = 0.0;
+    }
+}
@@ -5,8 +5,53 @@ use std::collections::{HashMap, HashSet};
 use url::Url;
 use uuid::Uuid;
 
+mod annotations;
+mod contributor;
+mod dedup;
+mod diff;
 #[cfg(feature = "fb2")]
 mod fb2;
+mod hyphenation;
+#[cfg(feature = "images")]
+mod images;
+mod isbn;
+mod language;
+mod layout;
+mod list;
+#[cfg(any(feature = "fb2", feature = "images"))]
+mod placeholder;
+mod position;
+mod remap;
+mod search;
+mod sequence;
+mod spans;
+mod stats;
+mod toc;
+mod typography;
+mod walk;
+
+pub use annotations::{Annotations, Bookmark, Highlight, Resolution, Status, UserNote};
+pub use dedup::ImageDedupReport;
+pub use diff::{
+    diff, BlockChange, BookDiff, ChapterChange, FootnoteChange, MetadataChange, TextChange,
+};
+#[cfg(feature = "fb2")]
+pub use fb2::{Fb2Options, Fb2Style};
+pub use hyphenation::SOFT_HYPHEN;
+#[cfg(feature = "images")]
+pub use images::{ImageProfile, VariantFormat};
+pub use layout::{
+    Layout, LayoutOptions, MonospaceMeasurer, Page, Run, RunContent, TextMeasurer, TextStyle,
+    Viewport,
+};
+pub use position::{Part, Position};
+pub use remap::{remap_positions, RemappedPosition};
+pub use search::{SearchHit, SearchIndex};
+pub use sequence::sort_by_series;
+pub use spans::SpanReport;
+pub use stats::{BookStats, ChapterStats, ReadingSpeed, Stats};
+pub use toc::{TocEntry, TocOptions};
+pub use typography::TypographyOptions;
 
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub struct Book {
@@ -16,6 +61,20 @@ pub struct Book {
     pub short_title: String,
     pub date: Date,
     pub authors: Vec<Author>,
+    /// Translators, editors, illustrators and others who worked on this edition
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub contributors: Vec<Contributor>,
+    /// Identifiers of this edition, such as ISBN
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub identifiers: Vec<BookIdentifier>,
+    /// Publication of this edition, unlike [`Book::date`] of the original work
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publication: Option<Publication>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rights: Option<Rights>,
+    /// Series and collections the book belongs to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sequences: Vec<Sequence>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cover: Option<InlineImage>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -29,6 +88,26 @@ pub struct Book {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub comments: Option<Footnotes>,
     pub chapters: Vec<Chapter>,
+    /// Prepared variants of the resources, such as images, by the id of the resource
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub resources: HashMap<Uuid, Resource>,
+}
+
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Resource {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<ResourceVariant>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct ResourceVariant {
+    pub id: Uuid,
+    pub mime_type: String,
+    pub width: u32,
+    pub height: u32,
+    /// Converted to shades of gray, for e-ink screens
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub grayscale: bool,
 }
 
 #[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
@@ -51,6 +130,78 @@ pub struct Author {
     pub middle_name: Option<String>,
 }
 
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Contributor {
+    pub person: Author,
+    pub roles: Vec<ContributorRole>,
+}
+
+/// Roles by their MARC relator codes, see [`ContributorRole::marc_code`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "&'static str")]
+pub enum ContributorRole {
+    Translator,
+    Editor,
+    Illustrator,
+    Narrator,
+    Compiler,
+    /// Maker of the electronic edition
+    BookProducer,
+    Publisher,
+    Contributor,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum BookIdentifier {
+    Isbn(Isbn),
+    /// Identifier in another scheme, e.g. ISSN, DOI or the id of the FB2 document
+    Other {
+        scheme: String,
+        value: String,
+    },
+}
+
+/// ISBN-10 or ISBN-13 with a valid check digit, stored without hyphens
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Isbn(String);
+
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Publication {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub city: Option<String>,
+    /// Date of this edition, often the year only
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<Date>,
+}
+
+#[derive(Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct Rights {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub copyright_holders: Vec<String>,
+    /// SPDX identifier, URL or text of the license
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Sequence {
+    pub name: String,
+    /// Place of the book in the sequence
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<LanguageTag>,
+    /// Series of the publisher of the paper book, rather than of the work itself
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub publisher: bool,
+    /// Sequences within this one, e.g. a cycle within collected works
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sequences: Vec<Sequence>,
+}
+
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub struct Footnotes {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -70,6 +221,10 @@ pub struct Chapter {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub anchor: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<LanguageTag>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<Direction>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<Title>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub annotation: Option<Annotation>,
@@ -88,8 +243,13 @@ pub enum Content {
     Subtitle(Paragraph),
     Cite(Cite),
     Table(Table),
+    List(List),
+    Preformatted(Preformatted),
+    Math(Math),
     Image(Image),
     EmptyLine,
+    /// Separator of scenes, usually shown as `* * *`
+    SceneBreak,
 }
 
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
@@ -106,6 +266,7 @@ pub enum AnnotationElement {
     Cite(Cite),
     Subtitle(Paragraph),
     Table(Table),
+    List(List),
     EmptyLine,
 }
 
@@ -130,6 +291,8 @@ pub enum EpigraphElement {
 pub struct Poem {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub anchor: Option<String>,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub classes: HashSet<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<Title>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
@@ -161,6 +324,12 @@ pub struct Stanza {
 pub struct Cite {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub anchor: Option<String>,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub classes: HashSet<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<LanguageTag>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<Direction>,
     // TODO: exclude images, something else?
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub authors: Vec<Paragraph>,
@@ -173,6 +342,7 @@ pub enum CiteElement {
     Poem(Poem),
     Subtitle(Paragraph),
     Table(Table),
+    List(List),
     EmptyLine,
 }
 
@@ -192,6 +362,12 @@ pub enum TitleElement {
 pub struct Paragraph {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub anchor: Option<String>,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub classes: HashSet<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<LanguageTag>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<Direction>,
     pub content: Vec<Span>,
 }
 
@@ -199,6 +375,10 @@ pub struct Paragraph {
 pub struct Table {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub anchor: Option<String>,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub classes: HashSet<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub caption: Option<Paragraph>,
     pub header_column: bool,
     pub header_row: bool,
     pub rows: Vec<TableRow>,
@@ -213,18 +393,120 @@ pub struct TableRow {
 pub struct TableCell {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub anchor: Option<String>,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub classes: HashSet<String>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub header: bool,
+    /// Number of columns the cell takes, 1 by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column_span: Option<u32>,
+    /// Number of rows the cell takes, 1 by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub row_span: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub horizontal_align: Option<HorizontalAlign>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vertical_align: Option<VerticalAlign>,
     pub content: Vec<Span>,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum HorizontalAlign {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum VerticalAlign {
+    Top,
+    Middle,
+    Bottom,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct List {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<String>,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub classes: HashSet<String>,
+    pub kind: ListKind,
+    /// Number of the first item of an ordered list, 1 by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<u32>,
+    pub items: Vec<ListItem>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum ListKind {
+    Ordered,
+    Unordered,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct ListItem {
+    /// Blocks of the item, including nested lists
+    pub content: Vec<Content>,
+}
+
+/// Code listings and ASCII art, the whitespace of the lines is kept as is
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Preformatted {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<String>,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub classes: HashSet<String>,
+    /// Language of the code, e.g. `rust`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    pub lines: Vec<String>,
+}
+
+/// A formula as MathML and/or TeX, with the alt text for readers that cannot render either
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Math {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<String>,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub classes: HashSet<String>,
+    /// The `<math>` element
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mathml: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tex: Option<String>,
+    pub alt: String,
+}
+
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
 pub struct Image {
     pub id: Uuid,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub anchor: Option<String>,
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub classes: HashSet<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub alt: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
+    /// Size in pixels, to reserve space before the image is loaded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    /// Shown while the image is loading
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<Placeholder>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub caption: Vec<Paragraph>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Placeholder {
+    /// Dominant color of the image as `#rrggbb`
+    Color(String),
+    BlurHash(String),
 }
 
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
@@ -232,6 +514,8 @@ pub enum Span {
     Footnote(FootnoteLink),
     Link(Link),
     Image(InlineImage),
+    Math(InlineMath),
+    Ruby(Ruby),
     Text(Text),
 }
 
@@ -275,6 +559,33 @@ pub struct InlineImage {
     pub id: Uuid,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub alt: Option<String>,
+    /// Size in pixels, to reserve space before the image is loaded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    /// Shown while the image is loading
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<Placeholder>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct InlineMath {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mathml: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tex: Option<String>,
+    pub alt: String,
+}
+
+/// Pronunciation guide over the base text, e.g. furigana or stress marks.
+/// Readers without ruby support show it as "base(annotation)".
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+pub struct Ruby {
+    pub base: Vec<Text>,
+    pub annotation: String,
 }
 
 #[derive(Debug, PartialEq, Deserialize, Serialize)]
@@ -287,6 +598,16 @@ pub struct Text {
     pub decorations: HashSet<TextDecoration>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub baseline_shift: Option<BaselineShift>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<LanguageTag>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub direction: Option<Direction>,
+    /// Names of the styles of the source, e.g. FB2 named styles
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    pub classes: HashSet<String>,
+    /// Full form of the abbreviation that the text is, like the title of `<abbr>`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expansion: Option<String>,
     pub value: String,
 }
 
@@ -297,6 +618,10 @@ impl From<String> for Text {
             font_style: HashSet::new(),
             decorations: HashSet::new(),
             baseline_shift: None,
+            language: None,
+            direction: None,
+            classes: HashSet::new(),
+            expansion: None,
             value,
         }
     }
@@ -306,11 +631,14 @@ impl From<String> for Text {
 pub enum FontStyle {
     Italic,
     Code,
+    SmallCaps,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
 pub enum TextDecoration {
     LineThrough,
+    Underline,
+    Highlight,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
@@ -318,3 +646,15 @@ pub enum BaselineShift {
     Subscript,
     Superscript,
 }
+
+/// Direction of the text, `Auto` means it is detected from the text itself
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+pub enum Direction {
+    Ltr,
+    Rtl,
+    Auto,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
@@ -0,0 +1,60 @@
+use std::collections::HashSet;
+
+use crate::{Content, List, ListKind, Paragraph, Span, Text};
+
+/// Indent of nested lists in formats without lists, such as FB2
+const NESTED_INDENT: &str = "\u{a0}\u{a0}\u{a0}\u{a0}";
+
+impl List {
+    /// Lowers the list to paragraphs starting with the number or the bullet of the item,
+    /// for formats without lists, such as FB2
+    pub fn into_paragraphs(self) -> Vec<Content> {
+        let mut content = vec![];
+        lower(self, 0, &mut content);
+        content
+    }
+}
+
+fn lower(list: List, depth: usize, content: &mut Vec<Content>) {
+    let mut anchor = list.anchor;
+    let start = list.start.unwrap_or(1);
+    for (i, item) in list.items.into_iter().enumerate() {
+        let marker = match list.kind {
+            ListKind::Ordered => format!("{}. ", start.saturating_add(i as u32)),
+            ListKind::Unordered => "• ".to_string(),
+        };
+        let mut marked = false;
+        if !matches!(item.content.first(), Some(Content::Paragraph(_))) {
+            // the item starts with something else, so the number gets its own paragraph
+            marked = true;
+            content.push(Content::Paragraph(Paragraph {
+                anchor: anchor.take(),
+                classes: HashSet::new(),
+                language: None,
+                direction: None,
+                content: vec![Span::Text(Text::from(
+                    NESTED_INDENT.repeat(depth) + marker.trim_end(),
+                ))],
+            }));
+        }
+        for block in item.content {
+            match block {
+                Content::List(nested) => lower(nested, depth + 1, content),
+                Content::Paragraph(mut paragraph) => {
+                    let prefix = if marked {
+                        NESTED_INDENT.repeat(depth + 1)
+                    } else {
+                        NESTED_INDENT.repeat(depth) + &marker
+                    };
+                    marked = true;
+                    paragraph.content.insert(0, Span::Text(Text::from(prefix)));
+                    if paragraph.anchor.is_none() {
+                        paragraph.anchor = anchor.take();
+                    }
+                    content.push(Content::Paragraph(paragraph));
+                }
+                block => content.push(block),
+            }
+        }
+    }
+}
@@ -0,0 +1,31 @@
+use std::collections::HashMap;
+
+use image::DynamicImage;
+
+// the most common of the coarse colors of a thumbnail, as `#rrggbb`; transparent pixels are skipped
+pub(crate) fn dominant_color(image: &DynamicImage) -> Option<String> {
+    // smaller images are not scaled up, which would blend the colors of neighbouring pixels
+    let pixels = if image.width() > 32 || image.height() > 32 {
+        image.thumbnail(32, 32).to_rgba8()
+    } else {
+        image.to_rgba8()
+    };
+    let mut buckets: HashMap<[u8; 3], (u32, [u32; 3])> = HashMap::new();
+    for pixel in pixels.pixels() {
+        let [r, g, b, a] = pixel.0;
+        if a < 128 {
+            continue;
+        }
+        let (count, sum) = buckets.entry([r >> 4, g >> 4, b >> 4]).or_default();
+        *count += 1;
+        for (sum, channel) in sum.iter_mut().zip([r, g, b]) {
+            *sum += u32::from(channel);
+        }
+    }
+    // ties go to the darker bucket, so that the result does not depend on the hash order
+    let (_, (count, sum)) = buckets
+        .into_iter()
+        .max_by_key(|(bucket, (count, _))| (*count, std::cmp::Reverse(*bucket)))?;
+    let [r, g, b] = sum.map(|s| s / count);
+    Some(format!("#{r:02x}{g:02x}{b:02x}"))
+}
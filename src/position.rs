@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+
+use crate::walk::{parts, PartBlocks};
+use crate::{Book, Chapter};
+
+/// A point in the text of a book that stays valid for as long as the book is not edited
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+pub struct Position {
+    pub part: Part,
+    /// Index of the block in reading order within the part
+    pub block: usize,
+    /// Offset in characters within the plain text of the block
+    pub offset: usize,
+}
+
+impl Position {
+    pub fn new(part: Part, block: usize, offset: usize) -> Position {
+        Position {
+            part,
+            block,
+            offset,
+        }
+    }
+
+    pub fn start_of(part: Part) -> Position {
+        Position::new(part, 0, 0)
+    }
+}
+
+/// A part of a book that is read independently of the others
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize, Serialize)]
+pub enum Part {
+    /// The book annotation
+    Annotation,
+    /// The book title and epigraphs
+    Front,
+    /// A chapter (without its sub-chapters) by its indices in `chapters` and `sub_chapters`
+    Chapter(Vec<usize>),
+    Note(String),
+    Comment(String),
+}
+
+impl Book {
+    /// The chapter by its indices in `chapters` and `sub_chapters`, as in [`Part::Chapter`]
+    pub fn chapter(&self, path: &[usize]) -> Option<&Chapter> {
+        let (first, rest) = path.split_first()?;
+        let mut chapter = self.chapters.get(*first)?;
+        for &i in rest {
+            chapter = chapter.sub_chapters.get(i)?;
+        }
+        Some(chapter)
+    }
+
+    /// Plain text between two positions of the same part, blocks are separated by line breaks
+    pub fn text_between(&self, start: &Position, end: &Position) -> Option<String> {
+        text_between(&parts(self), start, end)
+    }
+}
+
+pub(crate) fn text_between(
+    parts: &[PartBlocks],
+    start: &Position,
+    end: &Position,
+) -> Option<String> {
+    if start.part != end.part || start > end {
+        return None;
+    }
+    let part = parts.iter().find(|p| p.part == start.part)?;
+    let blocks = part.blocks.get(start.block..=end.block)?;
+    let last = blocks.len() - 1;
+    let mut text = String::new();
+    for (i, block) in blocks.iter().enumerate() {
+        let chars = block.text().chars().collect::<Vec<_>>();
+        let from = if i == 0 { start.offset } else { 0 };
+        let to = if i == last { end.offset } else { chars.len() };
+        if i > 0 {
+            text.push('\n');
+        }
+        text.extend(chars.get(from..to)?);
+    }
+    Some(text)
+}
@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use similar::{capture_diff_slices, get_diff_ratio, Algorithm, DiffTag};
+
+use crate::walk::parts;
+use crate::{Book, Part, Position};
+
+/// Characters of the text after the position that have to match exactly
+const EXACT_LEN: usize = 16;
+/// Characters of context before and after the exact quote
+const CONTEXT_LEN: usize = 32;
+/// Blocks less similar than this are not considered revisions of each other
+const MIN_SIMILARITY: f32 = 0.5;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RemappedPosition {
+    pub position: Position,
+    /// From 0 to 1, where 1 means the text around the position has not changed
+    pub confidence: f32,
+}
+
+/// Finds the positions of the old revision of a book in the new one, by anchors and by the text around them.
+/// Positions that cannot be found are `None`.
+pub fn remap_positions(
+    old: &Book,
+    new: &Book,
+    positions: &[Position],
+) -> Vec<Option<RemappedPosition>> {
+    let old = TextBlocks::new(old);
+    let new = TextBlocks::new(new);
+    positions.iter().map(|p| remap(&old, &new, p)).collect()
+}
+
+struct TextBlock<'a> {
+    part: Part,
+    block: usize,
+    anchor: Option<&'a str>,
+    text: Vec<char>,
+}
+
+struct TextBlocks<'a> {
+    blocks: Vec<TextBlock<'a>>,
+    by_position: HashMap<(Part, usize), usize>,
+    by_anchor: HashMap<&'a str, usize>,
+}
+
+impl<'a> TextBlocks<'a> {
+    fn new(book: &'a Book) -> TextBlocks<'a> {
+        let mut blocks = vec![];
+        let mut by_anchor = HashMap::new();
+        for part in parts(book) {
+            if let Some(anchor) = part.anchor {
+                by_anchor.insert(anchor, blocks.len());
+            }
+            for (i, block) in part.blocks.iter().enumerate() {
+                if let Some(anchor) = block.anchor {
+                    by_anchor.insert(anchor, blocks.len());
+                }
+                blocks.push(TextBlock {
+                    part: part.part.clone(),
+                    block: i,
+                    anchor: block.anchor.or(part.anchor.filter(|_| i == 0)),
+                    text: block.text().chars().collect(),
+                });
+            }
+        }
+        let by_position = blocks
+            .iter()
+            .enumerate()
+            .map(|(i, b)| ((b.part.clone(), b.block), i))
+            .collect();
+        TextBlocks {
+            blocks,
+            by_position,
+            by_anchor,
+        }
+    }
+
+    fn get(&self, part: &Part, block: usize) -> Option<(usize, &TextBlock<'a>)> {
+        let i = *self.by_position.get(&(part.clone(), block))?;
+        Some((i, &self.blocks[i]))
+    }
+
+    // the closest anchor at or before the block within its part
+    fn anchor_before(&self, index: usize) -> Option<&'a str> {
+        let part = &self.blocks[index].part;
+        self.blocks[..=index]
+            .iter()
+            .rev()
+            .take_while(|b| &b.part == part)
+            .find_map(|b| b.anchor)
+    }
+}
+
+fn remap(old: &TextBlocks, new: &TextBlocks, position: &Position) -> Option<RemappedPosition> {
+    let (index, block) = old.get(&position.part, position.block)?;
+    let offset = position.offset.min(block.text.len());
+
+    if let Some((_, same)) = new.get(&position.part, position.block) {
+        if same.text == block.text {
+            return Some(RemappedPosition {
+                position: Position::new(position.part.clone(), position.block, offset),
+                confidence: 1.0,
+            });
+        }
+    }
+
+    let anchor = old.anchor_before(index);
+    let anchored = anchor.and_then(|a| new.by_anchor.get(a)).copied();
+    // the part of the new book where the position most likely is
+    let region = anchored
+        .map(|i| &new.blocks[i].part)
+        .or_else(|| new.get(&position.part, 0).map(|(_, b)| &b.part));
+
+    let quote = TextQuote::new(&block.text, offset);
+    let exact = new
+        .blocks
+        .iter()
+        .flat_map(|b| {
+            quote
+                .find(&b.text)
+                .map(move |(offset, score)| (b, offset, score))
+        })
+        .map(|(b, offset, score)| {
+            let regional = region == Some(&b.part);
+            (
+                b,
+                offset,
+                0.6 + 0.3 * score + if regional { 0.1 } else { 0.0 },
+            )
+        })
+        .max_by(|a, b| a.2.total_cmp(&b.2));
+    if let Some((b, offset, confidence)) = exact {
+        return Some(RemappedPosition {
+            position: Position::new(b.part.clone(), b.block, offset),
+            confidence: confidence.min(1.0),
+        });
+    }
+
+    // the text at the position was edited, so the block is matched as a whole
+    let own_anchor = anchored.filter(|_| block.anchor.is_some() && anchor == block.anchor);
+    let (b, similarity) = match own_anchor {
+        Some(i) => (&new.blocks[i], similarity(&block.text, &new.blocks[i].text)),
+        None => new
+            .blocks
+            .iter()
+            .filter(|b| region.is_none_or(|r| r == &b.part))
+            .map(|b| (b, similarity(&block.text, &b.text)))
+            .filter(|&(_, s)| s >= MIN_SIMILARITY)
+            .max_by(|a, b| a.1.total_cmp(&b.1))?,
+    };
+    Some(RemappedPosition {
+        position: Position::new(
+            b.part.clone(),
+            b.block,
+            map_offset(&block.text, &b.text, offset),
+        ),
+        confidence: 0.6 * similarity,
+    })
+}
+
+// the text at a position with its context, like a text quote selector of web annotations
+struct TextQuote<'a> {
+    prefix: &'a [char],
+    exact: &'a [char],
+    suffix: &'a [char],
+}
+
+impl<'a> TextQuote<'a> {
+    fn new(text: &'a [char], offset: usize) -> TextQuote<'a> {
+        let exact_end = (offset + EXACT_LEN).min(text.len());
+        TextQuote {
+            prefix: &text[offset.saturating_sub(CONTEXT_LEN)..offset],
+            exact: &text[offset..exact_end],
+            suffix: &text[exact_end..(exact_end + CONTEXT_LEN).min(text.len())],
+        }
+    }
+
+    // the best occurrence of the exact quote in the text and how well its context matches
+    fn find(&self, text: &[char]) -> Option<(usize, f32)> {
+        if self.exact.is_empty() && self.prefix.is_empty() {
+            return None;
+        }
+        let needle = if self.exact.is_empty() {
+            self.prefix
+        } else {
+            self.exact
+        };
+        (0..=text.len().checked_sub(needle.len())?)
+            .filter(|&i| text[i..].starts_with(needle))
+            .map(|i| {
+                let offset = if self.exact.is_empty() {
+                    i + needle.len()
+                } else {
+                    i
+                };
+                (offset, self.context_score(text, offset))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    fn context_score(&self, text: &[char], offset: usize) -> f32 {
+        let total = self.prefix.len() + self.suffix.len();
+        if total == 0 {
+            return 1.0;
+        }
+        let before = text[..offset].iter().rev();
+        let prefix = before
+            .zip(self.prefix.iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let suffix_start = (offset + self.exact.len()).min(text.len());
+        let suffix = text[suffix_start..]
+            .iter()
+            .zip(self.suffix)
+            .take_while(|(a, b)| a == b)
+            .count();
+        (prefix + suffix) as f32 / total as f32
+    }
+}
+
+fn similarity(old: &[char], new: &[char]) -> f32 {
+    let ops = capture_diff_slices(Algorithm::Myers, old, new);
+    get_diff_ratio(&ops, old.len(), new.len())
+}
+
+// the offset in the new text of the character at the offset in the old text
+fn map_offset(old: &[char], new: &[char], offset: usize) -> usize {
+    for op in capture_diff_slices(Algorithm::Myers, old, new) {
+        let (old_range, new_range) = (op.old_range(), op.new_range());
+        if old_range.contains(&offset) {
+            let shift = (offset - old_range.start).min(new_range.len());
+            return match op.tag() {
+                DiffTag::Equal | DiffTag::Replace => new_range.start + shift,
+                DiffTag::Delete | DiffTag::Insert => new_range.start,
+            };
+        }
+    }
+    new.len()
+}
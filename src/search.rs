@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
+
+use crate::walk::{parts, BlockKind};
+use crate::{Book, Part, Position, SOFT_HYPHEN};
+
+/// Characters of context around a hit
+const SNIPPET_CONTEXT: usize = 40;
+
+/// Full-text index of a book, serializable to be shipped alongside it
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SearchIndex {
+    blocks: Vec<IndexedBlock>,
+    /// Folded word to its occurrences as indices of the block and the word in the block
+    words: HashMap<String, Vec<(usize, usize)>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+struct IndexedBlock {
+    part: Part,
+    block: usize,
+    text: String,
+    /// Character ranges of the words in the text
+    words: Vec<(usize, usize)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SearchHit {
+    pub start: Position,
+    pub end: Position,
+    /// Text of the block before the match, trimmed to whole words
+    pub before: String,
+    pub matched: String,
+    /// Text of the block after the match, trimmed to whole words
+    pub after: String,
+}
+
+impl SearchIndex {
+    /// Indexes the text of every part of the book, including notes, comments and table cells
+    pub fn new(book: &Book) -> SearchIndex {
+        let mut index = SearchIndex {
+            blocks: vec![],
+            words: HashMap::new(),
+        };
+        for part in parts(book) {
+            for (i, block) in part.blocks.iter().enumerate() {
+                if !matches!(
+                    block.kind,
+                    BlockKind::Text(_) | BlockKind::Verbatim(_) | BlockKind::Math(_)
+                ) {
+                    continue;
+                }
+                let text = block.text();
+                let chars = text.chars().collect::<Vec<_>>();
+                let words = words(&chars);
+                for (w, &(start, end)) in words.iter().enumerate() {
+                    index
+                        .words
+                        .entry(fold(&chars[start..end]))
+                        .or_default()
+                        .push((index.blocks.len(), w));
+                }
+                if !words.is_empty() {
+                    index.blocks.push(IndexedBlock {
+                        part: part.part.clone(),
+                        block: i,
+                        text,
+                        words,
+                    });
+                }
+            }
+        }
+        index
+    }
+
+    /// Finds the phrase in reading order, ignoring case, diacritics and punctuation
+    pub fn search(&self, query: &str) -> Vec<SearchHit> {
+        let query = query.chars().collect::<Vec<_>>();
+        let query = words(&query)
+            .into_iter()
+            .map(|(start, end)| fold(&query[start..end]))
+            .collect::<Vec<_>>();
+        let Some(occurrences) = query.first().and_then(|w| self.words.get(w)) else {
+            return vec![];
+        };
+        let mut hits = vec![];
+        for &(b, w) in occurrences {
+            let block = &self.blocks[b];
+            let Some(words) = block.words.get(w..w + query.len()) else {
+                continue;
+            };
+            let chars = block.text.chars().collect::<Vec<_>>();
+            let matches = words
+                .iter()
+                .zip(&query)
+                .skip(1)
+                .all(|(&(start, end), q)| fold(&chars[start..end]) == *q);
+            if matches {
+                let start = words[0].0;
+                let end = words[words.len() - 1].1;
+                hits.push(block.hit(&chars, start, end));
+            }
+        }
+        hits
+    }
+}
+
+impl IndexedBlock {
+    fn hit(&self, chars: &[char], start: usize, end: usize) -> SearchHit {
+        let mut before = start.saturating_sub(SNIPPET_CONTEXT);
+        if before > 0 {
+            if let Some(space) = chars[before..start].iter().position(|c| c.is_whitespace()) {
+                before += space + 1;
+            }
+        }
+        let mut after = (end + SNIPPET_CONTEXT).min(chars.len());
+        if after < chars.len() {
+            if let Some(space) = chars[end..after].iter().rposition(|c| c.is_whitespace()) {
+                after = end + space;
+            }
+        }
+        SearchHit {
+            start: Position::new(self.part.clone(), self.block, start),
+            end: Position::new(self.part.clone(), self.block, end),
+            before: chars[before..start].iter().collect(),
+            matched: chars[start..end].iter().collect(),
+            after: chars[end..after].iter().collect(),
+        }
+    }
+}
+
+// character ranges of letters and digits, soft hyphens and accents do not break words
+fn words(chars: &[char]) -> Vec<(usize, usize)> {
+    let mut words = vec![];
+    let mut start = None;
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_alphanumeric() || (start.is_some() && (c == SOFT_HYPHEN || is_combining_mark(c))) {
+            start.get_or_insert(i);
+        } else if let Some(start) = start.take() {
+            words.push((start, i));
+        }
+    }
+    if let Some(start) = start {
+        words.push((start, chars.len()));
+    }
+    words
+}
+
+// lowercase without diacritics, so that "ё" matches "е", but "й" stays a separate letter
+fn fold(word: &[char]) -> String {
+    let mut folded = String::with_capacity(word.len());
+    for &c in word {
+        decompose_canonical(c, |c| {
+            if c == '\u{0306}' && folded.ends_with('и') {
+                folded.pop();
+                folded.push('й');
+            } else if c != SOFT_HYPHEN && !is_combining_mark(c) {
+                folded.extend(c.to_lowercase());
+            }
+        });
+    }
+    folded
+}
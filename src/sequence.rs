@@ -0,0 +1,56 @@
+use std::cmp::Ordering;
+
+use crate::{Book, Sequence};
+
+/// Sorts books by series: by the name of the series, then by the number in it,
+/// nested sequences included. The series of the work is preferred to the series
+/// of the publisher. Books without a series go last, ties are sorted by title.
+pub fn sort_by_series(books: &mut [Book]) {
+    books.sort_by(|a, b| {
+        compare_keys(series_key(a), series_key(b)).then_with(|| {
+            a.short_title
+                .to_lowercase()
+                .cmp(&b.short_title.to_lowercase())
+        })
+    });
+}
+
+// names and numbers along the first chain of nested sequences
+fn series_key(book: &Book) -> Option<Vec<(String, Option<u32>)>> {
+    let first = book
+        .sequences
+        .iter()
+        .find(|s| !s.publisher)
+        .or(book.sequences.first())?;
+    let mut key = vec![];
+    let mut sequence: Option<&Sequence> = Some(first);
+    while let Some(s) = sequence {
+        key.push((s.name.to_lowercase(), s.number));
+        sequence = s.sequences.first();
+    }
+    Some(key)
+}
+
+fn compare_keys(
+    a: Option<Vec<(String, Option<u32>)>>,
+    b: Option<Vec<(String, Option<u32>)>>,
+) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            for ((a_name, a_number), (b_name, b_number)) in a.iter().zip(&b) {
+                // unnumbered books go after the numbered ones
+                let ordering = a_name
+                    .cmp(b_name)
+                    .then_with(|| a_number.is_none().cmp(&b_number.is_none()))
+                    .then_with(|| a_number.cmp(b_number));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            a.len().cmp(&b.len())
+        }
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
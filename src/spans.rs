@@ -0,0 +1,179 @@
+use crate::walk::for_each_spans_mut;
+use crate::{Book, FontStyle, Span, Text};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SpanReport {
+    pub spans_before: usize,
+    pub spans_after: usize,
+    pub text_bytes_before: usize,
+    pub text_bytes_after: usize,
+}
+
+impl Book {
+    /// Merges adjacent texts of the same style, collapses whitespace and drops empty texts.
+    /// Code is left intact, footnotes, links and ruby are kept even if their text is empty.
+    pub fn normalize_spans(&mut self) -> SpanReport {
+        let mut report = SpanReport::default();
+        for_each_spans_mut(self, &mut |spans| {
+            report.spans_before += spans.len();
+            report.text_bytes_before += text_bytes(spans);
+            collapse_whitespace(spans);
+            spans.retain(|span| !matches!(span, Span::Text(t) if t.value.is_empty()));
+            for span in spans.iter_mut() {
+                match span {
+                    Span::Footnote(f) => merge_texts(&mut f.content),
+                    Span::Link(l) => merge_texts(&mut l.content),
+                    Span::Ruby(r) => merge_texts(&mut r.base),
+                    Span::Image(_) | Span::Math(_) | Span::Text(_) => {}
+                }
+            }
+            merge_spans(spans);
+            report.spans_after += spans.len();
+            report.text_bytes_after += text_bytes(spans);
+        });
+        report
+    }
+}
+
+fn texts_mut(span: &mut Span) -> &mut [Text] {
+    match span {
+        Span::Footnote(f) => &mut f.content,
+        Span::Link(l) => &mut l.content,
+        Span::Ruby(r) => &mut r.base,
+        Span::Image(_) | Span::Math(_) => &mut [],
+        Span::Text(t) => std::slice::from_mut(t),
+    }
+}
+
+fn text_bytes(spans: &[Span]) -> usize {
+    let bytes = |texts: &[Text]| texts.iter().map(|t| t.value.len()).sum::<usize>();
+    spans
+        .iter()
+        .map(|span| match span {
+            Span::Footnote(f) => bytes(&f.content),
+            Span::Link(l) => bytes(&l.content),
+            Span::Image(_) => 0,
+            Span::Math(m) => m.alt.len(),
+            Span::Ruby(r) => bytes(&r.base) + r.annotation.len(),
+            Span::Text(t) => t.value.len(),
+        })
+        .sum()
+}
+
+// XML whitespace, so that non-breaking spaces are kept
+fn is_collapsible(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r')
+}
+
+fn is_code(text: &Text) -> bool {
+    text.font_style.contains(&FontStyle::Code)
+}
+
+// collapses whitespace across text boundaries and trims it at the paragraph edges
+fn collapse_whitespace(spans: &mut [Span]) {
+    let mut after_space = true;
+    for span in spans.iter_mut() {
+        if let Span::Image(_) | Span::Math(_) = span {
+            after_space = false;
+        }
+        for text in texts_mut(span) {
+            if is_code(text) {
+                after_space = false;
+                continue;
+            }
+            let mut value = String::with_capacity(text.value.len());
+            for c in text.value.chars() {
+                if !is_collapsible(c) {
+                    value.push(c);
+                    after_space = false;
+                } else if !after_space {
+                    value.push(' ');
+                    after_space = true;
+                }
+            }
+            if value != text.value {
+                text.value = value;
+            }
+        }
+        if let Span::Ruby(_) = span {
+            after_space = false;
+        }
+    }
+    'trim: for span in spans.iter_mut().rev() {
+        if let Span::Image(_) | Span::Math(_) | Span::Ruby(_) = span {
+            break;
+        }
+        for text in texts_mut(span).iter_mut().rev() {
+            if is_code(text) {
+                break 'trim;
+            }
+            let trimmed = text.value.trim_end_matches(' ').len();
+            text.value.truncate(trimmed);
+            if !text.value.is_empty() {
+                break 'trim;
+            }
+        }
+    }
+}
+
+fn merge_texts(texts: &mut Vec<Text>) {
+    let mut merged: Vec<Text> = Vec::with_capacity(texts.len());
+    for text in texts.drain(..).filter(|t| !t.value.is_empty()) {
+        if let Some(text) = append(merged.last_mut(), text) {
+            merged.push(text);
+        }
+    }
+    *texts = merged;
+}
+
+fn merge_spans(spans: &mut Vec<Span>) {
+    let mut merged: Vec<Span> = Vec::with_capacity(spans.len());
+    for span in spans.drain(..) {
+        let span = match (merged.last_mut(), span) {
+            (Some(Span::Text(last)), Span::Text(text)) => match append(Some(last), text) {
+                Some(text) => Span::Text(text),
+                None => continue,
+            },
+            (_, span) => span,
+        };
+        merged.push(span);
+    }
+    *spans = merged;
+}
+
+// appends the text to the previous one if they look the same, otherwise gives it back
+fn append(last: Option<&mut Text>, text: Text) -> Option<Text> {
+    let Some(last) = last else {
+        return Some(text);
+    };
+    if same_style(last, &text) || (is_blank(&text) && is_plain(&text) && is_plain(last)) {
+        last.value.push_str(&text.value);
+        None
+    } else if is_blank(last) && is_plain(last) && is_plain(&text) {
+        let blank = std::mem::replace(last, text);
+        last.value.insert_str(0, &blank.value);
+        None
+    } else {
+        Some(text)
+    }
+}
+
+fn same_style(a: &Text, b: &Text) -> bool {
+    a.font_weight == b.font_weight
+        && a.font_style == b.font_style
+        && a.decorations == b.decorations
+        && a.baseline_shift == b.baseline_shift
+        && a.language == b.language
+        && a.direction == b.direction
+        && a.classes == b.classes
+        && a.expansion == b.expansion
+}
+
+fn is_blank(text: &Text) -> bool {
+    text.value.chars().all(is_collapsible)
+}
+
+// whitespace looks the same in any style, unless it is decorated or monospaced
+fn is_plain(text: &Text) -> bool {
+    text.decorations.is_empty() && !is_code(text)
+}
@@ -0,0 +1,166 @@
+use std::ops::AddAssign;
+use std::time::Duration;
+
+use language_tags::LanguageTag;
+use serde::{Deserialize, Serialize};
+
+use crate::toc::title_text;
+use crate::walk::{count_words, parts, Block, BlockKind, Role};
+use crate::{Book, Part, Span, SOFT_HYPHEN};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Stats {
+    pub words: usize,
+    /// Characters of the text including spaces
+    pub characters: usize,
+    pub paragraphs: usize,
+    /// Block and inline images
+    pub images: usize,
+    /// Links to notes and comments
+    pub footnotes: usize,
+    pub tables: usize,
+    pub poems: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ChapterStats {
+    pub part: Part,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The chapter itself, without sub-chapters
+    pub stats: Stats,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct BookStats {
+    /// The whole book including the annotation, notes and comments
+    pub total: Stats,
+    /// Chapters in reading order
+    pub chapters: Vec<ChapterStats>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ReadingSpeed {
+    /// Ideographs and kana count as words, see [`Stats::words`],
+    /// so for Chinese and Japanese this is characters per minute
+    pub words_per_minute: f32,
+}
+
+impl ReadingSpeed {
+    /// Average silent reading speed of adults for the language
+    pub fn for_language(language: Option<&LanguageTag>) -> ReadingSpeed {
+        let language = language.map(|l| l.primary_language().to_ascii_lowercase());
+        // Trauzettel-Klosinski S, Dietz K. Standardized assessment of reading performance, 2012,
+        // characters per minute for Chinese and Japanese
+        let words_per_minute = match language.as_deref() {
+            Some("ar") => 138.0,
+            Some("de") => 179.0,
+            Some("en") => 228.0,
+            Some("es") => 218.0,
+            Some("fi") => 161.0,
+            Some("fr") => 195.0,
+            Some("he") => 187.0,
+            Some("it") => 188.0,
+            Some("ja") => 357.0,
+            Some("nl") => 202.0,
+            Some("pl") => 166.0,
+            Some("pt") => 181.0,
+            Some("ru") => 184.0,
+            Some("sl") => 180.0,
+            Some("sv") => 199.0,
+            Some("tr") => 166.0,
+            Some("zh") => 255.0,
+            _ => 184.0,
+        };
+        ReadingSpeed { words_per_minute }
+    }
+}
+
+impl Stats {
+    /// `None` if the speed is not a positive number
+    pub fn reading_time(&self, speed: &ReadingSpeed) -> Option<Duration> {
+        if speed.words_per_minute > 0.0 {
+            Duration::try_from_secs_f32(self.words as f32 / speed.words_per_minute * 60.0).ok()
+        } else {
+            None
+        }
+    }
+
+    fn add_block(&mut self, block: &Block) {
+        match block.kind {
+            BlockKind::Text(spans) => {
+                let text = block.text();
+                self.words += count_words(&text);
+                self.characters += text.chars().filter(|&c| c != SOFT_HYPHEN).count();
+                for span in spans {
+                    match span {
+                        Span::Footnote(_) => self.footnotes += 1,
+                        Span::Image(_) => self.images += 1,
+                        Span::Link(_) | Span::Math(_) | Span::Ruby(_) | Span::Text(_) => {}
+                    }
+                }
+            }
+            BlockKind::Verbatim(_) | BlockKind::Math(_) => {
+                let text = block.text();
+                self.words += count_words(&text);
+                self.characters += text.chars().count();
+            }
+            BlockKind::EmptyLine | BlockKind::SceneBreak => {}
+            BlockKind::Image(_) => self.images += 1,
+        }
+        match block.role {
+            Role::Paragraph | Role::ListItem { .. } if matches!(block.kind, BlockKind::Text(_)) => {
+                self.paragraphs += 1
+            }
+            Role::TableCell {
+                row: 0, column: 0, ..
+            } => self.tables += 1,
+            Role::Verse {
+                first: true,
+                stanza: 0,
+            } => self.poems += 1,
+            _ => {}
+        }
+    }
+}
+
+impl AddAssign<&Stats> for Stats {
+    fn add_assign(&mut self, other: &Stats) {
+        self.words += other.words;
+        self.characters += other.characters;
+        self.paragraphs += other.paragraphs;
+        self.images += other.images;
+        self.footnotes += other.footnotes;
+        self.tables += other.tables;
+        self.poems += other.poems;
+    }
+}
+
+impl Book {
+    pub fn stats(&self) -> BookStats {
+        let mut total = Stats::default();
+        let mut chapters = vec![];
+        for part in parts(self) {
+            let mut stats = Stats::default();
+            part.blocks.iter().for_each(|b| stats.add_block(b));
+            total += &stats;
+            if let Part::Chapter(path) = &part.part {
+                let title = self
+                    .chapter(path)
+                    .and_then(|c| c.title.as_ref())
+                    .map(title_text)
+                    .filter(|t| !t.is_empty());
+                chapters.push(ChapterStats {
+                    part: part.part,
+                    title,
+                    stats,
+                });
+            }
+        }
+        BookStats { total, chapters }
+    }
+
+    pub fn reading_speed(&self) -> ReadingSpeed {
+        ReadingSpeed::for_language(self.language.as_ref())
+    }
+}
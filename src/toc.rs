@@ -0,0 +1,115 @@
+use serde::{Deserialize, Serialize};
+
+use crate::walk::{chapter_blocks, count_words, plain_text};
+use crate::{Book, Chapter, Part, Position, Title, TitleElement};
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct TocEntry {
+    /// Zero for top-level chapters
+    pub depth: usize,
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub anchor: Option<String>,
+    pub position: Position,
+    /// Words of the chapter including all of its sub-chapters
+    pub word_count: usize,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<TocEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TocOptions {
+    /// Label for chapters without a title, `{n}` is replaced with the number of the chapter among its siblings
+    pub untitled: String,
+    /// Chapters nested deeper than this are left out
+    pub max_depth: Option<usize>,
+}
+
+impl Default for TocOptions {
+    fn default() -> Self {
+        TocOptions {
+            untitled: "{n}".to_string(),
+            max_depth: None,
+        }
+    }
+}
+
+impl Book {
+    pub fn toc(&self, options: &TocOptions) -> Vec<TocEntry> {
+        let mut path = vec![];
+        toc_entries(&self.chapters, &mut path, options)
+    }
+}
+
+fn toc_entries(chapters: &[Chapter], path: &mut Vec<usize>, options: &TocOptions) -> Vec<TocEntry> {
+    let mut entries = vec![];
+    for (i, chapter) in chapters.iter().enumerate() {
+        path.push(i);
+        entries.push(toc_entry(chapter, path, options));
+        path.pop();
+    }
+    entries
+}
+
+fn toc_entry(chapter: &Chapter, path: &mut Vec<usize>, options: &TocOptions) -> TocEntry {
+    let depth = path.len() - 1;
+    let title = chapter
+        .title
+        .as_ref()
+        .map(title_text)
+        .filter(|t| !t.is_empty())
+        .unwrap_or_else(|| {
+            let n = path.last().map(|i| i + 1).unwrap_or(1);
+            options.untitled.replace("{n}", &n.to_string())
+        });
+    let children = if options.max_depth.is_none_or(|max| depth < max) {
+        toc_entries(&chapter.sub_chapters, path, options)
+    } else {
+        vec![]
+    };
+    TocEntry {
+        depth,
+        title,
+        anchor: chapter.anchor.clone(),
+        position: Position::start_of(Part::Chapter(path.clone())),
+        word_count: chapter_word_count(chapter),
+        children,
+    }
+}
+
+fn chapter_word_count(chapter: &Chapter) -> usize {
+    let own = chapter_blocks(chapter)
+        .iter()
+        .map(|block| count_words(&block.text()))
+        .sum::<usize>();
+    own + chapter
+        .sub_chapters
+        .iter()
+        .map(chapter_word_count)
+        .sum::<usize>()
+}
+
+// paragraphs of the title on a single line, separated by a period unless they already end with punctuation
+pub(crate) fn title_text(title: &Title) -> String {
+    let mut text = String::new();
+    for element in &title.content {
+        let TitleElement::Paragraph(p) = element else {
+            continue;
+        };
+        let line = plain_text(&p.content)
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(last) = text.chars().last() {
+            if !matches!(last, '.' | '!' | '?' | '…' | ':' | ';' | ',') {
+                text.push('.');
+            }
+            text.push(' ');
+        }
+        text.push_str(&line);
+    }
+    text
+}
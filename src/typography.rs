@@ -0,0 +1,280 @@
+use language_tags::LanguageTag;
+
+use crate::hyphenation::is_url;
+use crate::walk::for_each_spans_with_language_mut;
+use crate::{Book, FontStyle, Href, Span, Text};
+
+const NBSP: char = '\u{00A0}';
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypographyOptions {
+    /// Straight quotes to the quotes of the language, e.g. «» and „“ for Russian, “” and ‘’ for English
+    pub quotes: bool,
+    /// Spaced hyphens to em dashes, hyphens in number ranges to en dashes
+    pub dashes: bool,
+    /// Three dots to the ellipsis character
+    pub ellipses: bool,
+    /// Runs of spaces to a single space
+    pub spaces: bool,
+    /// Non-breaking spaces before dashes and after single-letter words
+    pub non_breaking_spaces: bool,
+}
+
+impl Default for TypographyOptions {
+    fn default() -> Self {
+        TypographyOptions {
+            quotes: true,
+            dashes: true,
+            ellipses: true,
+            spaces: true,
+            non_breaking_spaces: true,
+        }
+    }
+}
+
+impl Book {
+    /// Normalizes quotes, dashes, spaces and ellipses in the text,
+    /// except for code, URLs and the text of remote links.
+    /// Quotes follow the language of the paragraph, inherited from the cite, chapter or the book.
+    pub fn normalize_typography(&mut self, options: &TypographyOptions) {
+        for_each_spans_with_language_mut(self, &mut |spans, language| {
+            let quotes = Quotes::for_language(language);
+            // texts with a flag whether they are kept as is
+            let mut texts = vec![];
+            for span in spans.iter_mut() {
+                match span {
+                    Span::Footnote(f) => texts.extend(f.content.iter_mut().map(|t| (t, false))),
+                    Span::Link(l) => {
+                        let remote = matches!(l.href, Href::Remote(_));
+                        texts.extend(l.content.iter_mut().map(|t| (t, remote)));
+                    }
+                    Span::Image(_) | Span::Math(_) | Span::Ruby(_) => {}
+                    Span::Text(t) => texts.push((t, false)),
+                }
+            }
+            normalize(&mut texts, options, &quotes);
+        });
+    }
+}
+
+struct Quotes {
+    outer: (char, char),
+    inner: (char, char),
+}
+
+impl Quotes {
+    fn for_language(language: Option<&LanguageTag>) -> Quotes {
+        let language = language.map(|l| l.primary_language().to_ascii_lowercase());
+        match language.as_deref() {
+            Some("ru" | "uk" | "be") => Quotes {
+                outer: ('«', '»'),
+                inner: ('„', '“'),
+            },
+            Some("de" | "cs" | "sk" | "bg" | "lt") => Quotes {
+                outer: ('„', '“'),
+                inner: ('‚', '‘'),
+            },
+            _ => Quotes {
+                outer: ('“', '”'),
+                inner: ('‘', '’'),
+            },
+        }
+    }
+}
+
+// the texts of a paragraph are normalized as a whole, so that quotes may open and close in different texts
+fn normalize(texts: &mut [(&mut Text, bool)], options: &TypographyOptions, quotes: &Quotes) {
+    let mut chars = vec![];
+    let mut frozen = vec![];
+    let mut lengths = vec![];
+    for (text, verbatim) in texts.iter() {
+        let code = text.font_style.contains(&FontStyle::Code);
+        let before = chars.len();
+        chars.extend(text.value.chars());
+        frozen.resize(chars.len(), code || *verbatim);
+        lengths.push(chars.len() - before);
+    }
+    freeze_urls(&chars, &mut frozen);
+
+    let mut output: Vec<String> = Vec::with_capacity(chars.len());
+    let mut depth = 0usize;
+    // whether the last quote was an opening one, so that ""word"" nests and "word"" closes twice
+    let mut opened_last = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if frozen[i] {
+            output.push(c.to_string());
+            i += 1;
+            continue;
+        }
+        let next = |n: usize| chars.get(i + n).copied().filter(|_| !frozen[i + n]);
+        let previous = last_char(&output);
+
+        if options.ellipses && c == '.' && next(1) == Some('.') && next(2) == Some('.') {
+            output.push('…'.to_string());
+            output.push(String::new());
+            output.push(String::new());
+            i += 3;
+            continue;
+        }
+
+        if options.dashes && matches!(c, '-' | '–' | '—') {
+            let length = if c == '-' && next(1) == Some('-') {
+                2
+            } else {
+                1
+            };
+            let after = chars.get(i + length).copied();
+            let spaced_before = previous.is_none_or(char::is_whitespace);
+            let spaced_after = after.is_none_or(char::is_whitespace);
+            if spaced_before && spaced_after {
+                let dialogue = previous.is_none();
+                if options.non_breaking_spaces && !dialogue {
+                    replace_last_space(&mut output, NBSP);
+                }
+                output.push('—'.to_string());
+                (1..length).for_each(|_| output.push(String::new()));
+                i += length;
+                // a dialogue line keeps the dash with the first word
+                if options.non_breaking_spaces && dialogue && chars.get(i) == Some(&' ') {
+                    output.push(NBSP.to_string());
+                    i += 1;
+                }
+                continue;
+            }
+            if length == 2 {
+                output.push('—'.to_string());
+                output.push(String::new());
+                i += 2;
+                continue;
+            }
+            if c == '-' && is_number_range(&chars, i) {
+                output.push('–'.to_string());
+                i += 1;
+                continue;
+            }
+        }
+
+        if options.quotes && c == '"' {
+            let opening = previous.is_none_or(|p| {
+                p.is_whitespace() || "([{—–-/".contains(p) || (opened_last && is_quote(p, quotes))
+            });
+            opened_last = opening;
+            let quote = if opening {
+                depth += 1;
+                if depth == 1 {
+                    quotes.outer.0
+                } else {
+                    quotes.inner.0
+                }
+            } else {
+                let quote = if depth > 1 {
+                    quotes.inner.1
+                } else {
+                    quotes.outer.1
+                };
+                depth = depth.saturating_sub(1);
+                quote
+            };
+            output.push(quote.to_string());
+            i += 1;
+            continue;
+        }
+
+        if c.is_whitespace() && c != '\n' {
+            if options.spaces && previous.is_some_and(|p| p == ' ' || p == NBSP) {
+                output.push(String::new());
+                i += 1;
+                continue;
+            }
+            if options.non_breaking_spaces && c == ' ' && after_single_letter(&output) {
+                output.push(NBSP.to_string());
+                i += 1;
+                continue;
+            }
+        }
+
+        output.push(c.to_string());
+        i += 1;
+    }
+
+    let mut output = output.into_iter();
+    for ((text, _), length) in texts.iter_mut().zip(lengths) {
+        let value = output.by_ref().take(length).collect::<String>();
+        if value != text.value {
+            text.value = value;
+        }
+    }
+}
+
+// whitespace-separated tokens that look like URLs or e-mails
+fn freeze_urls(chars: &[char], frozen: &mut [bool]) {
+    let mut start = 0;
+    for end in 0..=chars.len() {
+        if chars.get(end).is_none_or(|c| c.is_whitespace()) {
+            let token = chars[start..end].iter().collect::<String>();
+            if is_url(&token) {
+                frozen[start..end].fill(true);
+            }
+            start = end + 1;
+        }
+    }
+}
+
+// an ascending range of two numbers, such as 1914-1918 or 5-7, but not a date
+// like 2024-01-15 or a phone number like 555-1234
+fn is_number_range(chars: &[char], hyphen: usize) -> bool {
+    let is_part = |c: &char| c.is_ascii_digit() || *c == '-';
+    let start = chars[..hyphen]
+        .iter()
+        .rposition(|c| !is_part(c))
+        .map_or(0, |p| p + 1);
+    let end = chars[hyphen..]
+        .iter()
+        .position(|c| !is_part(c))
+        .map_or(chars.len(), |p| hyphen + p);
+    let from = chars[start..hyphen].iter().collect::<String>();
+    let to = chars[hyphen + 1..end].iter().collect::<String>();
+    if from.is_empty() || to.is_empty() || to.contains('-') {
+        return false;
+    }
+    if from.starts_with('0') || to.starts_with('0') || (from.len() == 3 && to.len() == 4) {
+        return false;
+    }
+    matches!((from.parse::<u64>(), to.parse::<u64>()), (Ok(from), Ok(to)) if from < to)
+}
+
+fn is_quote(c: char, quotes: &Quotes) -> bool {
+    [
+        quotes.outer.0,
+        quotes.outer.1,
+        quotes.inner.0,
+        quotes.inner.1,
+    ]
+    .contains(&c)
+}
+
+fn last_char(output: &[String]) -> Option<char> {
+    output.iter().rev().find_map(|s| s.chars().last())
+}
+
+fn replace_last_space(output: &mut [String], replacement: char) {
+    if let Some(last) = output.iter_mut().rev().find(|s| !s.is_empty()) {
+        if last == " " {
+            *last = replacement.to_string();
+        }
+    }
+}
+
+// a word of one letter, such as the Russian prepositions "в", "к", "с" or the English article "a"
+fn after_single_letter(output: &[String]) -> bool {
+    let mut chars = output.iter().rev().flat_map(|s| s.chars().rev());
+    match (chars.next(), chars.next()) {
+        (Some(letter), None) => letter.is_alphabetic(),
+        (Some(letter), Some(before)) => {
+            letter.is_alphabetic() && (before.is_whitespace() || "([{«„“‘\"".contains(before))
+        }
+        _ => false,
+    }
+}
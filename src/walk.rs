@@ -0,0 +1,814 @@
+use language_tags::LanguageTag;
+use uuid::Uuid;
+
+use crate::{
+    Annotation, AnnotationElement, Book, Chapter, Cite, CiteElement, Content, Direction, Epigraph,
+    EpigraphElement, Footnotes, HorizontalAlign, Image, List, ListKind, Math, Paragraph, Part,
+    Placeholder, Poem, PoemElement, Preformatted, Span, Table, Title, TitleElement, VerticalAlign,
+};
+
+pub(crate) struct PartBlocks<'a> {
+    pub part: Part,
+    pub anchor: Option<&'a str>,
+    pub blocks: Vec<Block<'a>>,
+}
+
+pub(crate) struct Block<'a> {
+    pub kind: BlockKind<'a>,
+    pub role: Role,
+    pub anchor: Option<&'a str>,
+    // nesting depth of annotations, epigraphs and cites
+    pub indent: u8,
+    // of the block or the closest element around it, including the book
+    pub language: Option<&'a LanguageTag>,
+    pub direction: Option<Direction>,
+}
+
+pub(crate) enum BlockKind<'a> {
+    Text(&'a [Span]),
+    /// A line of a preformatted block
+    Verbatim(&'a str),
+    Math(&'a Math),
+    EmptyLine,
+    SceneBreak,
+    Image(&'a Image),
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) enum Role {
+    Title,
+    Subtitle,
+    Paragraph,
+    /// `first` line of the stanza, `stanza` index within the poem
+    Verse {
+        first: bool,
+        stanza: usize,
+    },
+    TextAuthor,
+    Caption,
+    /// `row` and `column` of the grid, where cells spanning several rows and columns take more
+    TableCell {
+        row: usize,
+        column: usize,
+        column_span: usize,
+        header: bool,
+        horizontal_align: Option<HorizontalAlign>,
+        vertical_align: Option<VerticalAlign>,
+    },
+    /// The first paragraph of a list item, `number` is `None` in unordered lists
+    ListItem {
+        number: Option<u32>,
+    },
+    /// `first` line of a preformatted block
+    Preformatted {
+        first: bool,
+    },
+}
+
+impl Block<'_> {
+    pub fn text(&self) -> String {
+        match self.kind {
+            BlockKind::Text(spans) => plain_text(spans),
+            BlockKind::Verbatim(line) => line.to_string(),
+            BlockKind::Math(math) => math.alt.clone(),
+            BlockKind::EmptyLine | BlockKind::SceneBreak | BlockKind::Image(_) => String::new(),
+        }
+    }
+}
+
+// every part of the book in reading order
+pub(crate) fn parts(book: &Book) -> Vec<PartBlocks<'_>> {
+    let mut parts = vec![];
+    if let Some(annotation) = &book.annotation {
+        let mut walker = Walker::new(book.language.as_ref(), None);
+        walker.annotation(annotation);
+        parts.push(walker.finish(Part::Annotation));
+    }
+    if book.title.is_some() || !book.epigraphs.is_empty() {
+        let mut walker = Walker::new(book.language.as_ref(), None);
+        if let Some(title) = &book.title {
+            walker.title(title);
+        }
+        for epigraph in &book.epigraphs {
+            walker.epigraph(epigraph);
+        }
+        parts.push(walker.finish(Part::Front));
+    }
+    let mut path = vec![];
+    for (i, chapter) in book.chapters.iter().enumerate() {
+        path.push(i);
+        chapter_parts(
+            chapter,
+            (book.language.as_ref(), None),
+            &mut path,
+            &mut parts,
+        );
+        path.pop();
+    }
+    if let Some(notes) = &book.notes {
+        footnote_parts(book, notes, Part::Note, &mut parts);
+    }
+    if let Some(comments) = &book.comments {
+        footnote_parts(book, comments, Part::Comment, &mut parts);
+    }
+    parts
+}
+
+// sub-chapters inherit the language and the direction of their chapter
+fn chapter_parts<'a>(
+    chapter: &'a Chapter,
+    (language, direction): (Option<&'a LanguageTag>, Option<Direction>),
+    path: &mut Vec<usize>,
+    parts: &mut Vec<PartBlocks<'a>>,
+) {
+    let language = chapter.language.as_ref().or(language);
+    let direction = chapter.direction.or(direction);
+    parts.push(PartBlocks {
+        part: Part::Chapter(path.clone()),
+        anchor: chapter.anchor.as_deref(),
+        blocks: walk_chapter(chapter, Walker::new(language, direction)),
+    });
+    for (i, sub_chapter) in chapter.sub_chapters.iter().enumerate() {
+        path.push(i);
+        chapter_parts(sub_chapter, (language, direction), path, parts);
+        path.pop();
+    }
+}
+
+fn footnote_parts<'a>(
+    book: &'a Book,
+    footnotes: &'a Footnotes,
+    part: fn(String) -> Part,
+    parts: &mut Vec<PartBlocks<'a>>,
+) {
+    let mut ids = footnotes.content.keys().collect::<Vec<_>>();
+    ids.sort();
+    for id in ids {
+        let footnote = &footnotes.content[id];
+        let mut walker = Walker::new(book.language.as_ref(), None);
+        if let Some(title) = &footnote.title {
+            walker.title(title);
+        }
+        walker.contents(&footnote.content);
+        parts.push(walker.finish(part(id.clone())));
+    }
+}
+
+// blocks of the chapter itself, without sub-chapters
+pub(crate) fn chapter_blocks(chapter: &Chapter) -> Vec<Block<'_>> {
+    let walker = Walker::new(chapter.language.as_ref(), chapter.direction);
+    walk_chapter(chapter, walker)
+}
+
+fn walk_chapter<'a>(chapter: &'a Chapter, mut walker: Walker<'a>) -> Vec<Block<'a>> {
+    if let Some(title) = &chapter.title {
+        walker.title(title);
+    }
+    if let Some(annotation) = &chapter.annotation {
+        walker.annotation(annotation);
+    }
+    if let Some(cover) = &chapter.cover {
+        walker.image(cover);
+    }
+    for epigraph in &chapter.epigraphs {
+        walker.epigraph(epigraph);
+    }
+    walker.contents(&chapter.content);
+    walker.blocks
+}
+
+pub(crate) fn plain_text(spans: &[Span]) -> String {
+    let mut text = String::new();
+    for span in spans {
+        match span {
+            Span::Footnote(f) => f.content.iter().for_each(|t| text.push_str(&t.value)),
+            Span::Link(l) => l.content.iter().for_each(|t| text.push_str(&t.value)),
+            Span::Image(_) => {}
+            Span::Math(m) => text.push_str(&m.alt),
+            Span::Ruby(r) => {
+                r.base.iter().for_each(|t| text.push_str(&t.value));
+                text.push_str(&ruby_fallback(&r.annotation));
+            }
+            Span::Text(t) => text.push_str(&t.value),
+        }
+    }
+    text
+}
+
+// readers without ruby show the annotation after the base text
+pub(crate) fn ruby_fallback(annotation: &str) -> String {
+    format!("({annotation})")
+}
+
+/// The largest spans of a table cell, the same as in HTML
+pub(crate) const MAX_COLUMN_SPAN: u32 = 1000;
+pub(crate) const MAX_ROW_SPAN: u32 = 65534;
+
+// every ideograph and kana counts as a word, since Chinese and Japanese are written without spaces;
+// Thai, Lao, Khmer and Myanmar put spaces between phrases, so their words are estimated by length
+pub(crate) fn count_words(text: &str) -> usize {
+    text.split_whitespace()
+        .map(|w| {
+            let ideographs = w.chars().filter(|&c| is_ideograph(c)).count();
+            let unspaced = w.chars().filter(|&c| is_unspaced(c)).count();
+            let other = w
+                .chars()
+                .any(|c| c.is_alphanumeric() && !is_ideograph(c) && !is_unspaced(c));
+            ideographs + unspaced.div_ceil(UNSPACED_WORD_LENGTH) + usize::from(other)
+        })
+        .sum()
+}
+
+// average length of a word in characters, including vowel and tone marks
+const UNSPACED_WORD_LENGTH: usize = 5;
+
+// CJK ideographs, Hiragana and Katakana
+fn is_ideograph(c: char) -> bool {
+    matches!(
+        c,
+        '\u{3040}'..='\u{30FF}'
+            | '\u{3400}'..='\u{4DBF}'
+            | '\u{4E00}'..='\u{9FFF}'
+            | '\u{F900}'..='\u{FAFF}'
+            | '\u{20000}'..='\u{2FA1F}'
+    )
+}
+
+// Thai, Lao, Myanmar and Khmer with their extensions
+fn is_unspaced(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0E00}'..='\u{0EFF}'
+            | '\u{1000}'..='\u{109F}'
+            | '\u{1780}'..='\u{17FF}'
+            | '\u{19E0}'..='\u{19FF}'
+            | '\u{A9E0}'..='\u{A9FF}'
+            | '\u{AA60}'..='\u{AA7F}'
+    )
+}
+
+struct Walker<'a> {
+    blocks: Vec<Block<'a>>,
+    indent: u8,
+    language: Option<&'a LanguageTag>,
+    direction: Option<Direction>,
+}
+
+impl<'a> Walker<'a> {
+    fn new(language: Option<&'a LanguageTag>, direction: Option<Direction>) -> Walker<'a> {
+        Walker {
+            blocks: vec![],
+            indent: 0,
+            language,
+            direction,
+        }
+    }
+
+    fn finish(self, part: Part) -> PartBlocks<'a> {
+        PartBlocks {
+            part,
+            anchor: None,
+            blocks: self.blocks,
+        }
+    }
+
+    fn push(&mut self, kind: BlockKind<'a>, role: Role, anchor: Option<&'a str>) {
+        self.blocks.push(Block {
+            kind,
+            role,
+            anchor,
+            indent: self.indent,
+            language: self.language,
+            direction: self.direction,
+        });
+    }
+
+    fn paragraph(&mut self, paragraph: &'a Paragraph, role: Role) {
+        self.push(
+            BlockKind::Text(&paragraph.content),
+            role,
+            paragraph.anchor.as_deref(),
+        );
+        if let Some(block) = self.blocks.last_mut() {
+            block.language = paragraph.language.as_ref().or(block.language);
+            block.direction = paragraph.direction.or(block.direction);
+        }
+    }
+
+    fn empty_line(&mut self) {
+        self.push(BlockKind::EmptyLine, Role::Paragraph, None);
+    }
+
+    fn image(&mut self, image: &'a Image) {
+        self.push(
+            BlockKind::Image(image),
+            Role::Paragraph,
+            image.anchor.as_deref(),
+        );
+        for paragraph in &image.caption {
+            self.paragraph(paragraph, Role::Caption);
+        }
+    }
+
+    fn title(&mut self, title: &'a Title) {
+        for element in &title.content {
+            match element {
+                TitleElement::Paragraph(p) => self.paragraph(p, Role::Title),
+                TitleElement::EmptyLine => self.empty_line(),
+            }
+        }
+    }
+
+    fn contents(&mut self, contents: &'a [Content]) {
+        for content in contents {
+            match content {
+                Content::Paragraph(p) => self.paragraph(p, Role::Paragraph),
+                Content::Poem(p) => self.poem(p),
+                Content::Subtitle(p) => self.paragraph(p, Role::Subtitle),
+                Content::Cite(c) => self.cite(c),
+                Content::Table(t) => self.table(t),
+                Content::List(l) => self.list(l),
+                Content::Preformatted(p) => self.preformatted(p),
+                Content::Math(m) => {
+                    self.push(BlockKind::Math(m), Role::Paragraph, m.anchor.as_deref())
+                }
+                Content::Image(i) => self.image(i),
+                Content::EmptyLine => self.empty_line(),
+                Content::SceneBreak => self.push(BlockKind::SceneBreak, Role::Paragraph, None),
+            }
+        }
+    }
+
+    fn annotation(&mut self, annotation: &'a Annotation) {
+        self.indent += 1;
+        for element in &annotation.content {
+            match element {
+                AnnotationElement::Paragraph(p) => self.paragraph(p, Role::Paragraph),
+                AnnotationElement::Poem(p) => self.poem(p),
+                AnnotationElement::Cite(c) => self.cite(c),
+                AnnotationElement::Subtitle(p) => self.paragraph(p, Role::Subtitle),
+                AnnotationElement::Table(t) => self.table(t),
+                AnnotationElement::List(l) => self.list(l),
+                AnnotationElement::EmptyLine => self.empty_line(),
+            }
+        }
+        self.indent -= 1;
+    }
+
+    fn epigraph(&mut self, epigraph: &'a Epigraph) {
+        self.indent += 1;
+        for element in &epigraph.content {
+            match element {
+                EpigraphElement::Paragraph(p) => self.paragraph(p, Role::Paragraph),
+                EpigraphElement::Poem(p) => self.poem(p),
+                EpigraphElement::Cite(c) => self.cite(c),
+                EpigraphElement::EmptyLine => self.empty_line(),
+            }
+        }
+        for author in &epigraph.authors {
+            self.paragraph(author, Role::TextAuthor);
+        }
+        self.indent -= 1;
+    }
+
+    fn cite(&mut self, cite: &'a Cite) {
+        let around = (self.language, self.direction);
+        self.language = cite.language.as_ref().or(self.language);
+        self.direction = cite.direction.or(self.direction);
+        self.indent += 1;
+        for element in &cite.content {
+            match element {
+                CiteElement::Paragraph(p) => self.paragraph(p, Role::Paragraph),
+                CiteElement::Poem(p) => self.poem(p),
+                CiteElement::Subtitle(p) => self.paragraph(p, Role::Subtitle),
+                CiteElement::Table(t) => self.table(t),
+                CiteElement::List(l) => self.list(l),
+                CiteElement::EmptyLine => self.empty_line(),
+            }
+        }
+        for author in &cite.authors {
+            self.paragraph(author, Role::TextAuthor);
+        }
+        self.indent -= 1;
+        (self.language, self.direction) = around;
+    }
+
+    fn poem(&mut self, poem: &'a Poem) {
+        if let Some(title) = &poem.title {
+            self.title(title);
+        }
+        for epigraph in &poem.epigraphs {
+            self.epigraph(epigraph);
+        }
+        let mut stanza = 0;
+        for element in &poem.content {
+            match element {
+                PoemElement::Subtitle(p) => self.paragraph(p, Role::Subtitle),
+                PoemElement::Stanza(s) => {
+                    if let Some(title) = &s.title {
+                        self.title(title);
+                    }
+                    if let Some(subtitle) = &s.subtitle {
+                        self.paragraph(subtitle, Role::Subtitle);
+                    }
+                    for (i, line) in s.content.iter().enumerate() {
+                        let role = Role::Verse {
+                            first: i == 0,
+                            stanza,
+                        };
+                        self.paragraph(line, role);
+                    }
+                    stanza += 1;
+                }
+            }
+        }
+        for author in &poem.authors {
+            self.paragraph(author, Role::TextAuthor);
+        }
+    }
+
+    fn table(&mut self, table: &'a Table) {
+        if let Some(caption) = &table.caption {
+            self.paragraph(caption, Role::Caption);
+        }
+        let span = |span: Option<u32>, max: usize| (span.unwrap_or(1) as usize).min(max).max(1);
+        // a cell spans at most as many columns as there are cells in the longest row
+        let max_column_span = (MAX_COLUMN_SPAN as usize)
+            .min(table.rows.iter().map(|r| r.cells.len()).max().unwrap_or(0));
+        let columns = table
+            .rows
+            .iter()
+            .map(|r| {
+                r.cells
+                    .iter()
+                    .map(|c| span(c.column_span, max_column_span))
+                    .sum::<usize>()
+            })
+            .max()
+            .unwrap_or(0);
+        // rows left for the cells spanning rows above, by column
+        let mut spanned: Vec<usize> = vec![];
+        for (row, cells) in table.rows.iter().enumerate() {
+            let mut column = 0;
+            for cell in &cells.cells {
+                while spanned.get(column).is_some_and(|&rows| rows > 0) {
+                    column += 1;
+                }
+                // spans end at the last column and row of the table
+                let column_span = span(
+                    cell.column_span,
+                    max_column_span.min(columns.saturating_sub(column)),
+                );
+                let row_span = span(
+                    cell.row_span,
+                    (MAX_ROW_SPAN as usize).min(table.rows.len() - row),
+                );
+                if spanned.len() < column + column_span {
+                    spanned.resize(column + column_span, 0);
+                }
+                spanned[column..column + column_span].fill(row_span);
+                let role = Role::TableCell {
+                    row,
+                    column,
+                    column_span,
+                    header: cell.header,
+                    horizontal_align: cell.horizontal_align,
+                    vertical_align: cell.vertical_align,
+                };
+                self.push(BlockKind::Text(&cell.content), role, cell.anchor.as_deref());
+                column += column_span;
+            }
+            spanned
+                .iter_mut()
+                .for_each(|rows| *rows = rows.saturating_sub(1));
+        }
+    }
+
+    fn preformatted(&mut self, preformatted: &'a Preformatted) {
+        for (i, line) in preformatted.lines.iter().enumerate() {
+            let anchor = preformatted.anchor.as_deref().filter(|_| i == 0);
+            self.push(
+                BlockKind::Verbatim(line),
+                Role::Preformatted { first: i == 0 },
+                anchor,
+            );
+        }
+    }
+
+    fn list(&mut self, list: &'a List) {
+        self.indent += 1;
+        for (i, item) in list.items.iter().enumerate() {
+            let first = self.blocks.len();
+            self.contents(&item.content);
+            let number = match list.kind {
+                ListKind::Ordered => Some(list.start.unwrap_or(1).saturating_add(i as u32)),
+                ListKind::Unordered => None,
+            };
+            // nested lists and images at the start of an item go without a marker
+            if let Some(block) = self.blocks.get_mut(first) {
+                if block.role == Role::Paragraph && matches!(block.kind, BlockKind::Text(_)) {
+                    block.role = Role::ListItem { number };
+                }
+            }
+        }
+        self.indent -= 1;
+    }
+}
+
+// every sequence of spans in the book, i.e. paragraphs and table cells
+pub(crate) fn for_each_spans_mut(book: &mut Book, f: &mut impl FnMut(&mut Vec<Span>)) {
+    for_each_spans_with_language_mut(book, &mut |spans, _| f(spans));
+}
+
+/// Same as [`for_each_spans_mut`], with the language of the paragraph inherited from the
+/// cites, chapters and the book around it; texts may still override it
+pub(crate) fn for_each_spans_with_language_mut(
+    book: &mut Book,
+    f: &mut impl FnMut(&mut Vec<Span>, Option<&LanguageTag>),
+) {
+    let language = book.language.as_ref();
+    if let Some(annotation) = &mut book.annotation {
+        annotation_mut(annotation, language, f);
+    }
+    if let Some(title) = &mut book.title {
+        title_mut(title, language, f);
+    }
+    for epigraph in &mut book.epigraphs {
+        epigraph_mut(epigraph, language, f);
+    }
+    for chapter in &mut book.chapters {
+        chapter_mut(chapter, language, f);
+    }
+    for footnotes in [&mut book.notes, &mut book.comments].into_iter().flatten() {
+        if let Some(title) = &mut footnotes.title {
+            title_mut(title, language, f);
+        }
+        for footnote in footnotes.content.values_mut() {
+            if let Some(title) = &mut footnote.title {
+                title_mut(title, language, f);
+            }
+            contents_mut(&mut footnote.content, language, f);
+        }
+    }
+}
+
+/// Calls `f` with the id of every image of the book, including the cover
+pub(crate) fn for_each_image_id_mut(book: &mut Book, f: &mut impl FnMut(&mut Uuid)) {
+    for_each_image_mut(book, &mut |id, _| f(id));
+}
+
+/// Calls `f` with the id and the placeholder of every image of the book, including the cover
+pub(crate) fn for_each_image_mut(
+    book: &mut Book,
+    f: &mut impl FnMut(&mut Uuid, &mut Option<Placeholder>),
+) {
+    if let Some(cover) = &mut book.cover {
+        f(&mut cover.id, &mut cover.placeholder);
+    }
+    for_each_spans_mut(book, &mut |spans| {
+        for span in spans {
+            if let Span::Image(i) = span {
+                f(&mut i.id, &mut i.placeholder);
+            }
+        }
+    });
+    for chapter in &mut book.chapters {
+        chapter_images_mut(chapter, f);
+    }
+    for footnotes in [&mut book.notes, &mut book.comments].into_iter().flatten() {
+        for footnote in footnotes.content.values_mut() {
+            contents_images_mut(&mut footnote.content, f);
+        }
+    }
+}
+
+fn chapter_images_mut(
+    chapter: &mut Chapter,
+    f: &mut impl FnMut(&mut Uuid, &mut Option<Placeholder>),
+) {
+    if let Some(cover) = &mut chapter.cover {
+        f(&mut cover.id, &mut cover.placeholder);
+    }
+    contents_images_mut(&mut chapter.content, f);
+    for sub_chapter in &mut chapter.sub_chapters {
+        chapter_images_mut(sub_chapter, f);
+    }
+}
+
+fn contents_images_mut(
+    contents: &mut [Content],
+    f: &mut impl FnMut(&mut Uuid, &mut Option<Placeholder>),
+) {
+    for content in contents {
+        match content {
+            Content::Image(i) => f(&mut i.id, &mut i.placeholder),
+            Content::List(l) => {
+                for item in &mut l.items {
+                    contents_images_mut(&mut item.content, f);
+                }
+            }
+            Content::Paragraph(_)
+            | Content::Subtitle(_)
+            | Content::Poem(_)
+            | Content::Cite(_)
+            | Content::Table(_)
+            | Content::Preformatted(_)
+            | Content::Math(_)
+            | Content::EmptyLine
+            | Content::SceneBreak => {}
+        }
+    }
+}
+
+fn chapter_mut(
+    chapter: &mut Chapter,
+    language: Option<&LanguageTag>,
+    f: &mut impl FnMut(&mut Vec<Span>, Option<&LanguageTag>),
+) {
+    let language = chapter.language.as_ref().or(language);
+    if let Some(title) = &mut chapter.title {
+        title_mut(title, language, f);
+    }
+    if let Some(annotation) = &mut chapter.annotation {
+        annotation_mut(annotation, language, f);
+    }
+    if let Some(cover) = &mut chapter.cover {
+        image_mut(cover, language, f);
+    }
+    for epigraph in &mut chapter.epigraphs {
+        epigraph_mut(epigraph, language, f);
+    }
+    contents_mut(&mut chapter.content, language, f);
+    for sub_chapter in &mut chapter.sub_chapters {
+        chapter_mut(sub_chapter, language, f);
+    }
+}
+
+fn paragraph_mut(
+    paragraph: &mut Paragraph,
+    language: Option<&LanguageTag>,
+    f: &mut impl FnMut(&mut Vec<Span>, Option<&LanguageTag>),
+) {
+    f(
+        &mut paragraph.content,
+        paragraph.language.as_ref().or(language),
+    );
+}
+
+fn title_mut(
+    title: &mut Title,
+    language: Option<&LanguageTag>,
+    f: &mut impl FnMut(&mut Vec<Span>, Option<&LanguageTag>),
+) {
+    for element in &mut title.content {
+        match element {
+            TitleElement::Paragraph(p) => paragraph_mut(p, language, f),
+            TitleElement::EmptyLine => {}
+        }
+    }
+}
+
+fn contents_mut(
+    contents: &mut [Content],
+    language: Option<&LanguageTag>,
+    f: &mut impl FnMut(&mut Vec<Span>, Option<&LanguageTag>),
+) {
+    for content in contents {
+        match content {
+            Content::Paragraph(p) | Content::Subtitle(p) => paragraph_mut(p, language, f),
+            Content::Poem(p) => poem_mut(p, language, f),
+            Content::Cite(c) => cite_mut(c, language, f),
+            Content::Table(t) => table_mut(t, language, f),
+            Content::List(l) => list_mut(l, language, f),
+            Content::Image(i) => image_mut(i, language, f),
+            Content::Preformatted(_)
+            | Content::Math(_)
+            | Content::EmptyLine
+            | Content::SceneBreak => {}
+        }
+    }
+}
+
+fn annotation_mut(
+    annotation: &mut Annotation,
+    language: Option<&LanguageTag>,
+    f: &mut impl FnMut(&mut Vec<Span>, Option<&LanguageTag>),
+) {
+    for element in &mut annotation.content {
+        match element {
+            AnnotationElement::Paragraph(p) | AnnotationElement::Subtitle(p) => {
+                paragraph_mut(p, language, f)
+            }
+            AnnotationElement::Poem(p) => poem_mut(p, language, f),
+            AnnotationElement::Cite(c) => cite_mut(c, language, f),
+            AnnotationElement::Table(t) => table_mut(t, language, f),
+            AnnotationElement::List(l) => list_mut(l, language, f),
+            AnnotationElement::EmptyLine => {}
+        }
+    }
+}
+
+fn epigraph_mut(
+    epigraph: &mut Epigraph,
+    language: Option<&LanguageTag>,
+    f: &mut impl FnMut(&mut Vec<Span>, Option<&LanguageTag>),
+) {
+    for element in &mut epigraph.content {
+        match element {
+            EpigraphElement::Paragraph(p) => paragraph_mut(p, language, f),
+            EpigraphElement::Poem(p) => poem_mut(p, language, f),
+            EpigraphElement::Cite(c) => cite_mut(c, language, f),
+            EpigraphElement::EmptyLine => {}
+        }
+    }
+    for author in &mut epigraph.authors {
+        paragraph_mut(author, language, f);
+    }
+}
+
+fn cite_mut(
+    cite: &mut Cite,
+    language: Option<&LanguageTag>,
+    f: &mut impl FnMut(&mut Vec<Span>, Option<&LanguageTag>),
+) {
+    let language = cite.language.as_ref().or(language);
+    for element in &mut cite.content {
+        match element {
+            CiteElement::Paragraph(p) | CiteElement::Subtitle(p) => paragraph_mut(p, language, f),
+            CiteElement::Poem(p) => poem_mut(p, language, f),
+            CiteElement::Table(t) => table_mut(t, language, f),
+            CiteElement::List(l) => list_mut(l, language, f),
+            CiteElement::EmptyLine => {}
+        }
+    }
+    for author in &mut cite.authors {
+        paragraph_mut(author, language, f);
+    }
+}
+
+fn poem_mut(
+    poem: &mut Poem,
+    language: Option<&LanguageTag>,
+    f: &mut impl FnMut(&mut Vec<Span>, Option<&LanguageTag>),
+) {
+    if let Some(title) = &mut poem.title {
+        title_mut(title, language, f);
+    }
+    for epigraph in &mut poem.epigraphs {
+        epigraph_mut(epigraph, language, f);
+    }
+    for element in &mut poem.content {
+        match element {
+            PoemElement::Subtitle(p) => paragraph_mut(p, language, f),
+            PoemElement::Stanza(s) => {
+                if let Some(title) = &mut s.title {
+                    title_mut(title, language, f);
+                }
+                if let Some(subtitle) = &mut s.subtitle {
+                    paragraph_mut(subtitle, language, f);
+                }
+                for line in &mut s.content {
+                    paragraph_mut(line, language, f);
+                }
+            }
+        }
+    }
+    for author in &mut poem.authors {
+        paragraph_mut(author, language, f);
+    }
+}
+
+fn image_mut(
+    image: &mut Image,
+    language: Option<&LanguageTag>,
+    f: &mut impl FnMut(&mut Vec<Span>, Option<&LanguageTag>),
+) {
+    for paragraph in &mut image.caption {
+        paragraph_mut(paragraph, language, f);
+    }
+}
+
+fn table_mut(
+    table: &mut Table,
+    language: Option<&LanguageTag>,
+    f: &mut impl FnMut(&mut Vec<Span>, Option<&LanguageTag>),
+) {
+    if let Some(caption) = &mut table.caption {
+        paragraph_mut(caption, language, f);
+    }
+    for row in &mut table.rows {
+        for cell in &mut row.cells {
+            f(&mut cell.content, language);
+        }
+    }
+}
+
+fn list_mut(
+    list: &mut List,
+    language: Option<&LanguageTag>,
+    f: &mut impl FnMut(&mut Vec<Span>, Option<&LanguageTag>),
+) {
+    for item in &mut list.items {
+        contents_mut(&mut item.content, language, f);
+    }
+}
@@ -0,0 +1,156 @@
+use chrono::{DateTime, Utc};
+use json_book::{Annotations, Book, Bookmark, Highlight, Part, Position, Resolution, Status};
+use serde_json::json;
+use uuid::Uuid;
+
+mod common;
+
+use common::paragraph;
+
+fn book(id: &str) -> Book {
+    common::book_with(json!({
+        "id": id,
+        "chapters": [{
+            "content": [paragraph("A new first paragraph."), paragraph("Call me Ishmael. Some years ago")],
+            "sub_chapters": []
+        }]
+    }))
+}
+
+const BOOK_ID: &str = "00000000-0000-0000-0000-000000000001";
+
+fn time(seconds: i64) -> DateTime<Utc> {
+    DateTime::from_timestamp(seconds, 0).unwrap()
+}
+
+fn chapter(block: usize, offset: usize) -> Position {
+    Position::new(Part::Chapter(vec![0]), block, offset)
+}
+
+fn highlight(id: u128, start: Position, end: Position, quote: &str, modified: i64) -> Highlight {
+    Highlight {
+        id: Uuid::from_u128(id),
+        start,
+        end,
+        quote: quote.to_string(),
+        color: None,
+        created: time(0),
+        modified: time(modified),
+        deleted: false,
+    }
+}
+
+#[test]
+fn resolves_annotations() {
+    let mut annotations = Annotations::new(BOOK_ID.parse().unwrap());
+    annotations.bookmarks.push(Bookmark {
+        id: Uuid::from_u128(1),
+        position: chapter(5, 0),
+        created: time(0),
+        modified: time(0),
+        deleted: false,
+    });
+    annotations.highlights = vec![
+        highlight(2, chapter(1, 0), chapter(1, 15), "Call me Ishmael", 0),
+        // the book got a new first paragraph
+        highlight(3, chapter(0, 17), chapter(0, 27), "Some years", 0),
+        highlight(4, chapter(1, 0), chapter(1, 4), "Gone", 0),
+    ];
+    let resolutions = annotations.resolve(&book(BOOK_ID));
+    let status = |id: u128| {
+        resolutions
+            .iter()
+            .find(|r| r.id == Uuid::from_u128(id))
+            .map(|r| r.status.clone())
+    };
+    assert_eq!(status(1), Some(Status::Stale));
+    assert_eq!(status(2), Some(Status::Resolved));
+    assert_eq!(
+        status(3),
+        Some(Status::Relocated {
+            start: chapter(1, 17),
+            end: chapter(1, 27),
+        })
+    );
+    assert_eq!(status(4), Some(Status::Stale));
+
+    let other = annotations.resolve(&book("00000000-0000-0000-0000-000000000002"));
+    assert!(other.iter().all(|r| r.status == Status::Stale));
+}
+
+#[test]
+fn merges_annotations_from_devices() {
+    let book_id = BOOK_ID.parse().unwrap();
+    let mut phone = Annotations::new(book_id);
+    phone.highlights = vec![
+        highlight(1, chapter(1, 0), chapter(1, 4), "Call", 10),
+        highlight(2, chapter(1, 5), chapter(1, 7), "me", 10),
+    ];
+    let mut tablet = Annotations::new(book_id);
+    let mut recolored = highlight(1, chapter(1, 0), chapter(1, 4), "Call", 20);
+    recolored.color = Some("yellow".to_string());
+    let mut deleted = highlight(2, chapter(1, 5), chapter(1, 7), "me", 30);
+    deleted.deleted = true;
+    tablet.highlights = vec![deleted, recolored];
+    tablet
+        .highlights
+        .push(highlight(3, chapter(0, 0), chapter(0, 1), "A", 5));
+
+    let mut merged = phone.clone();
+    assert!(merged.merge(tablet.clone()));
+    let mut reversed = tablet.clone();
+    assert!(reversed.merge(phone.clone()));
+    assert_eq!(merged, reversed);
+
+    let ids = merged
+        .highlights
+        .iter()
+        .map(|h| h.id.as_u128())
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![3, 1, 2]);
+    assert_eq!(merged.highlights[1].color.as_deref(), Some("yellow"));
+    assert!(merged.highlights[2].deleted);
+    assert_eq!(
+        merged.resolve(&book(BOOK_ID)),
+        vec![
+            Resolution {
+                id: Uuid::from_u128(3),
+                status: Status::Resolved,
+            },
+            Resolution {
+                id: Uuid::from_u128(1),
+                status: Status::Resolved,
+            },
+        ]
+    );
+
+    let json = serde_json::to_value(&merged).unwrap();
+    assert_eq!(serde_json::from_value::<Annotations>(json).unwrap(), merged);
+    assert!(!merged.merge(Annotations::new(Uuid::nil())));
+}
+
+#[test]
+fn restores_annotations_edited_after_deletion() {
+    let book_id = BOOK_ID.parse().unwrap();
+    let mut phone = Annotations::new(book_id);
+    let mut deleted = highlight(1, chapter(1, 0), chapter(1, 4), "Call", 20);
+    deleted.deleted = true;
+    phone.highlights = vec![deleted.clone()];
+    let mut tablet = Annotations::new(book_id);
+    let mut recolored = highlight(1, chapter(1, 0), chapter(1, 4), "Call", 30);
+    recolored.color = Some("green".to_string());
+    tablet.highlights = vec![recolored.clone()];
+
+    let mut merged = phone.clone();
+    assert!(merged.merge(tablet.clone()));
+    assert_eq!(merged.highlights, vec![recolored.clone()]);
+    let mut reversed = tablet.clone();
+    assert!(reversed.merge(phone.clone()));
+    assert_eq!(reversed, merged);
+
+    // a deletion at the same time as the edit wins
+    deleted.modified = recolored.modified;
+    phone.highlights = vec![deleted.clone()];
+    assert!(phone.merge(tablet));
+    assert_eq!(phone.highlights, vec![deleted]);
+}
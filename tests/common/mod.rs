@@ -0,0 +1,41 @@
+//! JSON builders of the books used across the tests
+
+// every test crate uses only some of the builders
+#![allow(dead_code)]
+
+use json_book::Book;
+use serde_json::{json, Value};
+
+pub fn paragraph(text: &str) -> Value {
+    json!({ "Paragraph": { "content": [{ "Text": { "value": text } }] } })
+}
+
+/// Chapter without a title and sub-chapters
+pub fn chapter(content: Value) -> Value {
+    json!({ "content": content, "sub_chapters": [] })
+}
+
+/// Chapters of a book with a single paragraph of the spans
+pub fn single_paragraph(spans: Value) -> Value {
+    json!([chapter(json!([{ "Paragraph": { "content": spans } }]))])
+}
+
+pub fn book(chapters: Value) -> Book {
+    book_with(json!({ "chapters": chapters }))
+}
+
+/// Book with the fields replacing the defaults of a minimal book
+pub fn book_with(fields: Value) -> Book {
+    let mut book = json!({
+        "id": "00000000-0000-0000-0000-000000000000",
+        "short_title": "Book",
+        "date": {},
+        "authors": [],
+        "chapters": []
+    });
+    let Value::Object(fields) = fields else {
+        panic!("not an object: {fields}");
+    };
+    book.as_object_mut().unwrap().extend(fields);
+    serde_json::from_value(book).unwrap()
+}
@@ -0,0 +1,29 @@
+use json_book::ContributorRole;
+
+#[test]
+fn serializes_roles_as_marc_codes() {
+    let roles = [
+        ContributorRole::Translator,
+        ContributorRole::Editor,
+        ContributorRole::Illustrator,
+        ContributorRole::Narrator,
+        ContributorRole::Compiler,
+        ContributorRole::BookProducer,
+        ContributorRole::Publisher,
+        ContributorRole::Contributor,
+    ];
+    for role in roles {
+        let json = serde_json::to_value(role).unwrap();
+        assert_eq!(json, role.marc_code());
+        assert_eq!(
+            serde_json::from_value::<ContributorRole>(json).unwrap(),
+            role
+        );
+        assert_eq!(
+            ContributorRole::from_marc_code(role.marc_code()),
+            Some(role)
+        );
+    }
+    assert_eq!(ContributorRole::from_marc_code("aut"), None);
+    assert!(serde_json::from_str::<ContributorRole>("\"Translator\"").is_err());
+}
@@ -0,0 +1,49 @@
+use std::collections::HashMap;
+
+use json_book::{Content, Span};
+use serde_json::json;
+use uuid::Uuid;
+
+mod common;
+
+#[test]
+fn points_duplicate_images_to_one_id() {
+    let id = |n: u128| Uuid::from_u128(n);
+    let mut book = common::book_with(json!({
+        "cover": { "id": id(3) },
+        "chapters": [{
+            "content": [
+                { "Image": { "id": id(4) } },
+                { "Paragraph": { "content": [{ "Image": { "id": id(2) } }] } },
+                { "List": { "kind": "Unordered", "items": [{ "content": [
+                    { "Image": { "id": id(1) } }
+                ] }] } }
+            ],
+            "sub_chapters": []
+        }],
+        "resources": { id(4).to_string(): { "variants": [] } }
+    }));
+    let ornament = vec![1, 2, 3];
+    let resources = HashMap::from([
+        (id(1), ornament.clone()),
+        (id(2), vec![4, 5]),
+        (id(3), ornament.clone()),
+        (id(4), ornament),
+    ]);
+
+    let report = book.deduplicate_images(&resources);
+
+    assert_eq!(
+        report.replaced,
+        HashMap::from([(id(3), id(1)), (id(4), id(1))])
+    );
+    assert_eq!(report.bytes_saved, 6);
+    assert_eq!(book.cover.as_ref().unwrap().id, id(1));
+    let content = &book.chapters[0].content;
+    assert!(matches!(&content[0], Content::Image(i) if i.id == id(1)));
+    let Content::Paragraph(paragraph) = &content[1] else {
+        panic!("not a paragraph: {:?}", content[1]);
+    };
+    assert!(matches!(&paragraph.content[0], Span::Image(i) if i.id == id(2)));
+    assert_eq!(book.resources.keys().collect::<Vec<_>>(), vec![&id(1)]);
+}
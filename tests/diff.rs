@@ -0,0 +1,166 @@
+use json_book::{
+    diff, BlockChange, Book, BookDiff, ChapterChange, FootnoteChange, MetadataChange, Part,
+    Position, TextChange,
+};
+use serde_json::{json, Value};
+
+mod common;
+
+use common::paragraph;
+
+fn chapter(title: &str, content: Vec<Value>) -> Value {
+    json!({
+        "title": { "content": [paragraph(title)] },
+        "content": content,
+        "sub_chapters": []
+    })
+}
+
+fn book(short_title: &str, chapters: Vec<Value>, note: &str) -> Book {
+    common::book_with(json!({
+        "short_title": short_title,
+        "chapters": chapters,
+        "notes": { "content": { "n1": { "content": [paragraph(note)] } } }
+    }))
+}
+
+#[test]
+fn finds_no_changes_in_the_same_book() {
+    let book = || {
+        book(
+            "Book",
+            vec![chapter("One", vec![paragraph("Text")])],
+            "Note",
+        )
+    };
+    assert!(diff(&book(), &book()).is_empty());
+}
+
+#[test]
+fn reports_changes() {
+    let old = book(
+        "Book",
+        vec![
+            chapter(
+                "One",
+                vec![
+                    paragraph("The first paragraph stays"),
+                    paragraph("The second paragraph is edited here"),
+                    paragraph("The third paragraph is deleted"),
+                    paragraph("The fourth paragraph moves to chapter two"),
+                ],
+            ),
+            chapter("Two", vec![paragraph("Only paragraph")]),
+            chapter("Three", vec![paragraph("Gone with the chapter")]),
+        ],
+        "A note",
+    );
+    let new = book(
+        "Book, corrected",
+        vec![
+            chapter(
+                "One",
+                vec![
+                    paragraph("The first paragraph stays"),
+                    paragraph("The second paragraph was edited here"),
+                ],
+            ),
+            chapter(
+                "Two",
+                vec![
+                    paragraph("Only paragraph"),
+                    paragraph("The fourth paragraph moves to chapter two"),
+                    paragraph("A new paragraph"),
+                ],
+            ),
+        ],
+        "A corrected note",
+    );
+    let chapter = |c: usize, block: usize| Position::new(Part::Chapter(vec![c]), block, 0);
+    let equal = |text: &str| TextChange::Equal(text.to_string());
+    assert_eq!(
+        diff(&old, &new),
+        BookDiff {
+            metadata: vec![MetadataChange {
+                field: "short_title".to_string(),
+                old: Some("Book".to_string()),
+                new: Some("Book, corrected".to_string()),
+            }],
+            chapters: vec![ChapterChange::Deleted {
+                old: Part::Chapter(vec![2]),
+                title: Some("Three".to_string()),
+            }],
+            blocks: vec![
+                BlockChange::Edited {
+                    old: chapter(0, 2),
+                    new: chapter(0, 2),
+                    changes: vec![
+                        equal("The second paragraph "),
+                        TextChange::Deleted("is".to_string()),
+                        TextChange::Inserted("was".to_string()),
+                        equal(" edited here"),
+                    ],
+                },
+                BlockChange::Moved {
+                    old: chapter(0, 4),
+                    new: chapter(1, 2),
+                    text: "The fourth paragraph moves to chapter two".to_string(),
+                },
+                BlockChange::Deleted {
+                    old: chapter(0, 3),
+                    text: "The third paragraph is deleted".to_string(),
+                },
+                BlockChange::Deleted {
+                    old: chapter(2, 0),
+                    text: "Three".to_string(),
+                },
+                BlockChange::Deleted {
+                    old: chapter(2, 1),
+                    text: "Gone with the chapter".to_string(),
+                },
+                BlockChange::Inserted {
+                    new: chapter(1, 3),
+                    text: "A new paragraph".to_string(),
+                },
+            ],
+            footnotes: vec![FootnoteChange::Edited {
+                part: Part::Note("n1".to_string()),
+                changes: vec![
+                    equal("A "),
+                    TextChange::Inserted("corrected ".to_string()),
+                    equal("note"),
+                ],
+            }],
+        }
+    );
+}
+
+#[test]
+fn reports_publication_and_rights_changes() {
+    let old = common::book_with(json!({
+        "publication": { "city": "London", "date": { "iso_date": "1843-10-01" } },
+        "rights": { "copyright_holders": ["Analytical Press"] }
+    }));
+    let new = common::book_with(json!({
+        "publication": { "city": "Paris", "date": { "display_date": "1843" } },
+        "rights": { "copyright_holders": ["Analytical Press", "A. Lovelace"] }
+    }));
+    let change = |field: &str, old: Option<&str>, new: Option<&str>| MetadataChange {
+        field: field.to_string(),
+        old: old.map(str::to_string),
+        new: new.map(str::to_string),
+    };
+    assert_eq!(
+        diff(&old, &new).metadata,
+        vec![
+            change("publication.city", Some("London"), Some("Paris")),
+            change("publication.date.iso_date", Some("1843-10-01"), None),
+            change("publication.date.display_date", None, Some("1843")),
+            change(
+                "rights.copyright_holders",
+                Some("Analytical Press"),
+                Some("Analytical Press, A. Lovelace")
+            ),
+        ]
+    );
+}
@@ -0,0 +1,370 @@
+#![cfg(feature = "fb2")]
+
+use std::collections::HashMap;
+
+use json_book::{
+    Book, Content, ContributorRole, Fb2Options, Fb2Style, FontStyle, HorizontalAlign, Part,
+    Placeholder, Position, Span, TextDecoration, VerticalAlign,
+};
+use uuid::Uuid;
+
+fn from_fb2(body: &str) -> Book {
+    from_fb2_with_options(body, &Fb2Options::default())
+}
+
+fn from_fb2_with_options(body: &str, options: &Fb2Options) -> Book {
+    Book::from_fb2_with_options(
+        fiction_book(body, ""),
+        Uuid::nil(),
+        &HashMap::new(),
+        options,
+    )
+}
+
+fn fiction_book(body: &str, binaries: &str) -> fb2::FictionBook {
+    let xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<FictionBook xmlns="http://www.gribuser.ru/xml/fictionbook/2.0" xmlns:l="http://www.w3.org/1999/xlink">
+  <description>
+    <title-info>
+      <genre>comp_programming</genre>
+      <author><first-name>Ada</first-name><last-name>Lovelace</last-name></author>
+      <book-title>Book</book-title>
+      <lang>en</lang>
+    </title-info>
+    <document-info>
+      <author><nickname>editor</nickname></author>
+      <date>2024</date>
+      <id>book</id>
+      <version>1.0</version>
+    </document-info>
+  </description>
+  <body><section>{body}</section></body>
+  {binaries}
+</FictionBook>"#
+    );
+    quick_xml::de::from_str(&xml).unwrap()
+}
+
+#[test]
+fn groups_code_paragraphs() {
+    let book = from_fb2(
+        "<p>Run it:</p>\
+         <p><code>fn main() {</code></p>\
+         <p><code>&#160;&#160;&#160;&#160;println!(\"hi\");</code></p>\
+         <p><code>}</code></p>\
+         <p id=\"second\"><code>cargo run</code></p>\
+         <p>Done, <code>main</code> printed.</p>",
+    );
+    // the XML parser trims text, so FB2 listings indent with no-break spaces
+    let content = &book.chapters[0].content;
+    assert_eq!(content.len(), 4);
+    let Content::Preformatted(listing) = &content[1] else {
+        panic!("not preformatted: {:?}", content[1]);
+    };
+    assert_eq!(
+        listing.lines,
+        [
+            "fn main() {",
+            "\u{a0}\u{a0}\u{a0}\u{a0}println!(\"hi\");",
+            "}"
+        ]
+    );
+    assert!(
+        matches!(&content[2], Content::Preformatted(p) if p.anchor.as_deref() == Some("second"))
+    );
+    assert!(matches!(&content[3], Content::Paragraph(_)));
+}
+
+#[test]
+fn recognizes_scene_breaks() {
+    let book = from_fb2(
+        "<p>One.</p><empty-line/><subtitle>* * *</subtitle><empty-line/>\
+         <p>Two.</p><p>***</p>\
+         <p>Three.</p><p>&#8212;</p>\
+         <p>Four.</p><empty-line/><empty-line/>\
+         <p>Five.</p><empty-line/>\
+         <p>Six.</p><p>§</p><p>* *</p>\
+         <p id=\"scene\">⁂</p>",
+    );
+    let kinds = book.chapters[0]
+        .content
+        .iter()
+        .map(|c| match c {
+            Content::Paragraph(_) => "p",
+            Content::SceneBreak => "break",
+            Content::EmptyLine => "empty",
+            _ => "other",
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        [
+            "p", "break", "p", "break", "p", "break", "p", "break", "p", "empty", "p", "break",
+            "p", "p"
+        ]
+    );
+    let Some(Content::Paragraph(anchored)) = book.chapters[0].content.last() else {
+        panic!("not a paragraph: {:?}", book.chapters[0].content.last());
+    };
+    assert_eq!(anchored.anchor.as_deref(), Some("scene"));
+}
+
+#[test]
+fn keeps_table_cell_attributes() {
+    let book = from_fb2(
+        "<table>\
+           <tr align=\"center\"><th colspan=\"2\">Year</th><th rowspan=\"2\" valign=\"bottom\">Total</th></tr>\
+           <tr><td align=\"right\" style=\"total\">2023</td><td>2024</td></tr>\
+         </table>",
+    );
+    let Content::Table(table) = &book.chapters[0].content[0] else {
+        panic!("not a table: {:?}", book.chapters[0].content[0]);
+    };
+    let year = &table.rows[0].cells[0];
+    assert!(year.header);
+    assert_eq!(year.column_span, Some(2));
+    assert_eq!(year.horizontal_align, Some(HorizontalAlign::Center));
+    let total = &table.rows[0].cells[1];
+    assert_eq!(total.row_span, Some(2));
+    assert_eq!(total.vertical_align, Some(VerticalAlign::Bottom));
+    let cells = &table.rows[1].cells;
+    assert!(!cells[0].header);
+    assert_eq!(cells[0].horizontal_align, Some(HorizontalAlign::Right));
+    assert!(cells[0].classes.contains("total"));
+    assert!(cells[1].classes.is_empty());
+    assert_eq!(cells[1].horizontal_align, None);
+    assert_eq!(cells[1].column_span, None);
+}
+
+#[test]
+fn keeps_languages_of_sections_and_paragraphs() {
+    let book = from_fb2(
+        "<section xml:lang=\"de\"><p>Hallo</p><p xml:lang=\"fr\">Bonjour</p></section>\
+         <cite xml:lang=\"la\"><p>Carpe diem</p></cite>",
+    );
+    let language = |position| book.effective_language(&position).map(|l| l.to_string());
+    let chapter = |path: Vec<usize>, block| Position::new(Part::Chapter(path), block, 0);
+    assert_eq!(language(chapter(vec![0, 0], 0)).as_deref(), Some("de"));
+    assert_eq!(language(chapter(vec![0, 0], 1)).as_deref(), Some("fr"));
+    assert_eq!(language(chapter(vec![0], 0)).as_deref(), Some("la"));
+}
+
+#[test]
+fn maps_named_styles() {
+    let book = from_fb2(
+        "<p><style name=\"underline\">under</style><style name=\"SmallCaps\">Пушкин</style>\
+         <style name=\"letter\">plain</style></p>",
+    );
+    let Content::Paragraph(paragraph) = &book.chapters[0].content[0] else {
+        panic!("not a paragraph: {:?}", book.chapters[0].content[0]);
+    };
+    let texts = paragraph
+        .content
+        .iter()
+        .map(|s| match s {
+            Span::Text(t) => t,
+            _ => panic!("not a text: {s:?}"),
+        })
+        .collect::<Vec<_>>();
+    assert!(texts[0].decorations.contains(&TextDecoration::Underline));
+    assert!(texts[1].font_style.contains(&FontStyle::SmallCaps));
+    assert!(texts[2].font_style.is_empty() && texts[2].decorations.is_empty());
+    assert!(texts[2].classes.contains("letter"));
+    assert!(texts[0].classes.is_empty());
+}
+
+#[test]
+fn maps_custom_named_styles() {
+    let mut options = Fb2Options::default();
+    options.named_styles.insert(
+        "strikethrough".to_string(),
+        Fb2Style::Decoration(TextDecoration::LineThrough),
+    );
+    let book = from_fb2_with_options(
+        "<p style=\"sign\"><style name=\"Strikethrough\">gone</style></p>",
+        &options,
+    );
+    let Content::Paragraph(paragraph) = &book.chapters[0].content[0] else {
+        panic!("not a paragraph: {:?}", book.chapters[0].content[0]);
+    };
+    assert!(paragraph.classes.contains("sign"));
+    let Span::Text(text) = &paragraph.content[0] else {
+        panic!("not a text: {:?}", paragraph.content[0]);
+    };
+    assert!(text.decorations.contains(&TextDecoration::LineThrough));
+    assert!(text.classes.is_empty());
+}
+
+#[test]
+fn reads_image_metadata_from_binaries() {
+    let book = fiction_book(
+        "<image l:href=\"#map.png\"/><p><image l:href=\"#dot.gif\"/> dot</p>",
+        "<binary id=\"map.png\" content-type=\"image/png\">iVBORw0KGgoAAAANSUhEUgAAAAQAAAADCAIAAAA7ljmR\n\
+         AAAAEElEQVR42mMwTpsJRww4OQATIg5ZiRV5sgAAAABJRU5ErkJggg==</binary>\
+         <binary id=\"dot.gif\" content-type=\"\">R0lGODlhAwACAIAAAMwzAP///ywAAAAAAwACAAACBQRBEARRADs=</binary>",
+    );
+    let ids = HashMap::from([
+        ("map.png".to_string(), Uuid::from_u128(1)),
+        ("dot.gif".to_string(), Uuid::from_u128(2)),
+    ]);
+    let book = Book::from_fb2(book, Uuid::nil(), &ids);
+    // an image at the start of a section is its cover
+    let image = book.chapters[0].cover.as_ref().unwrap();
+    assert_eq!((image.width, image.height), (Some(4), Some(3)));
+    assert_eq!(image.mime_type.as_deref(), Some("image/png"));
+    assert_eq!(
+        image.placeholder,
+        Some(Placeholder::Color("#336699".to_string()))
+    );
+    let Content::Paragraph(paragraph) = &book.chapters[0].content[0] else {
+        panic!("not a paragraph: {:?}", book.chapters[0].content[0]);
+    };
+    let Span::Image(image) = &paragraph.content[0] else {
+        panic!("not an image: {:?}", paragraph.content[0]);
+    };
+    assert_eq!((image.width, image.height), (Some(3), Some(2)));
+    // without a content type, the type is recognized from the data
+    assert_eq!(image.mime_type.as_deref(), Some("image/gif"));
+    assert_eq!(
+        image.placeholder,
+        Some(Placeholder::Color("#cc3300".to_string()))
+    );
+}
+
+#[test]
+fn imports_nested_sequences() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<FictionBook xmlns="http://www.gribuser.ru/xml/fictionbook/2.0">
+  <description>
+    <title-info>
+      <genre>prose</genre>
+      <author><first-name>Лев</first-name><last-name>Толстой</last-name></author>
+      <book-title>Анна Каренина</book-title>
+      <lang>ru</lang>
+      <sequence name="Собрание сочинений" number="8"><sequence name="Романы" number="3"/></sequence>
+      <sequence name="" number="1"><sequence name="Классика"/></sequence>
+    </title-info>
+    <document-info>
+      <author><nickname>editor</nickname></author>
+      <date>2024</date>
+      <id>book</id>
+      <version>1.0</version>
+    </document-info>
+    <publish-info>
+      <publisher>Художественная литература</publisher>
+      <sequence name="Библиотека всемирной литературы" number="-1"/>
+    </publish-info>
+  </description>
+  <body><section><p>Все счастливые семьи похожи друг на друга.</p></section></body>
+</FictionBook>"#;
+    let book: fb2::FictionBook = quick_xml::de::from_str(xml).unwrap();
+    let book = Book::from_fb2(book, Uuid::nil(), &HashMap::new());
+    let sequences = serde_json::to_value(&book.sequences).unwrap();
+    assert_eq!(
+        sequences,
+        serde_json::json!([
+            {
+                "name": "Собрание сочинений",
+                "number": 8,
+                "sequences": [{ "name": "Романы", "number": 3 }]
+            },
+            // a sequence without a name gives its place to the nested ones
+            { "name": "Классика" },
+            { "name": "Библиотека всемирной литературы", "publisher": true }
+        ])
+    );
+}
+
+#[test]
+fn imports_translators_and_producers() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<FictionBook xmlns="http://www.gribuser.ru/xml/fictionbook/2.0">
+  <description>
+    <title-info>
+      <genre>prose</genre>
+      <author><first-name>Antoine</first-name><last-name>de Saint-Exupéry</last-name></author>
+      <book-title>Маленький принц</book-title>
+      <lang>ru</lang>
+      <translator><first-name>Нора</first-name><last-name>Галь</last-name></translator>
+    </title-info>
+    <document-info>
+      <author><nickname>ocr-team</nickname></author>
+      <author><first-name>Нора</first-name><last-name>Галь</last-name></author>
+      <date>2024</date>
+      <id>book</id>
+      <version>1.0</version>
+    </document-info>
+  </description>
+  <body><section><p>Когда мне было шесть лет...</p></section></body>
+</FictionBook>"#;
+    let book: fb2::FictionBook = quick_xml::de::from_str(xml).unwrap();
+    let book = Book::from_fb2(book, Uuid::nil(), &HashMap::new());
+    assert_eq!(book.authors.len(), 1);
+    let contributors: Vec<_> = book
+        .contributors
+        .iter()
+        .map(|c| (c.person.full_name.as_str(), c.roles.clone()))
+        .collect();
+    // the translator who also made the document is a single contributor
+    assert_eq!(
+        contributors,
+        vec![
+            (
+                "Нора Галь",
+                vec![ContributorRole::Translator, ContributorRole::BookProducer]
+            ),
+            ("ocr-team", vec![ContributorRole::BookProducer]),
+        ]
+    );
+}
+
+#[test]
+fn imports_publication_and_rights() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<FictionBook xmlns="http://www.gribuser.ru/xml/fictionbook/2.0">
+  <description>
+    <title-info>
+      <genre>prose</genre>
+      <author><first-name>Ada</first-name><last-name>Lovelace</last-name></author>
+      <book-title>Notes</book-title>
+      <lang>en</lang>
+    </title-info>
+    <document-info>
+      <author><nickname>editor</nickname></author>
+      <date>2024</date>
+      <id>6F1C1B2A-notes</id>
+      <version>1.0</version>
+      <publisher><first-name>Analytical</first-name><last-name>Press</last-name></publisher>
+    </document-info>
+    <publish-info>
+      <publisher>Analytical Press</publisher>
+      <city>London</city>
+      <year>1843</year>
+      <isbn>978-0-306-40615-7, 0-306-40615-3; 0-8044-2957-X</isbn>
+    </publish-info>
+    <custom-info info-type="License">CC-BY-4.0</custom-info>
+  </description>
+  <body><section><p>The Analytical Engine weaves algebraic patterns.</p></section></body>
+</FictionBook>"#;
+    let book: fb2::FictionBook = quick_xml::de::from_str(xml).unwrap();
+    let book = Book::from_fb2(book, Uuid::nil(), &HashMap::new());
+    // the ISBN with a wrong check digit is dropped
+    assert_eq!(
+        serde_json::to_value(&book.identifiers).unwrap(),
+        serde_json::json!([
+            { "Isbn": "9780306406157" },
+            { "Isbn": "080442957X" },
+            { "Other": { "scheme": "fb2", "value": "6F1C1B2A-notes" } }
+        ])
+    );
+    let publication = book.publication.unwrap();
+    assert_eq!(publication.publisher.as_deref(), Some("Analytical Press"));
+    assert_eq!(publication.city.as_deref(), Some("London"));
+    let date = publication.date.unwrap();
+    assert_eq!(date.iso_date, None);
+    assert_eq!(date.display_date.as_deref(), Some("1843"));
+    let rights = book.rights.unwrap();
+    assert_eq!(rights.copyright_holders, vec!["Analytical Press"]);
+    assert_eq!(rights.license.as_deref(), Some("CC-BY-4.0"));
+}
@@ -0,0 +1,93 @@
+#![cfg(feature = "hyphenation")]
+
+use json_book::{Book, SOFT_HYPHEN};
+use serde_json::{json, Value};
+
+mod common;
+
+fn book(language: &str, content: Value) -> Book {
+    common::book_with(json!({
+        "language": language,
+        "chapters": common::single_paragraph(content)
+    }))
+}
+
+fn texts(book: &Book) -> Value {
+    serde_json::to_value(&book.chapters[0].content).unwrap()[0]["Paragraph"]["content"].clone()
+}
+
+#[test]
+fn hyphenates_by_language() {
+    let mut book = book(
+        "ru",
+        json!([{ "Text": { "value": "Педагогическая поэма" } }]),
+    );
+    assert!(book.hyphenate());
+    let value = texts(&book)[0]["Text"]["value"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    assert!(value.contains(SOFT_HYPHEN));
+    assert_eq!(
+        value.replace(SOFT_HYPHEN, "-"),
+        "Пе-да-го-ги-че-ская по-э-ма"
+    );
+
+    let mut book = self::book("en-US", json!([{ "Text": { "value": "extensive" } }]));
+    assert!(book.hyphenate());
+    assert_eq!(
+        texts(&book)[0]["Text"]["value"],
+        format!("ex{SOFT_HYPHEN}ten{SOFT_HYPHEN}sive")
+    );
+
+    book.remove_soft_hyphens();
+    assert_eq!(texts(&book)[0]["Text"]["value"], "extensive");
+}
+
+#[test]
+fn skips_code_urls_and_external_links() {
+    let content = json!([
+        { "Text": { "font_style": ["Code"], "value": "extensive" } },
+        { "Text": { "value": "see https://example.com/extensive " } },
+        { "Link": {
+            "href": { "Remote": "https://example.com/" },
+            "content": [{ "value": "extensive" }]
+        } }
+    ]);
+    let mut book = book("en", content.clone());
+    assert!(book.hyphenate());
+    assert_eq!(texts(&book), content);
+}
+
+#[test]
+fn unsupported_language() {
+    let mut book = book("x-unknown", json!([{ "Text": { "value": "extensive" } }]));
+    assert!(!book.hyphenate());
+}
+
+#[test]
+fn hyphenates_by_language_of_paragraph_and_text() {
+    let mut book = common::book_with(json!({
+        "language": "ru",
+        "chapters": [common::chapter(json!([{ "Paragraph": {
+            "language": "en",
+            "content": [
+                { "Text": { "value": "internationalization " } },
+                { "Text": { "value": "поэма", "language": "ru" } }
+            ]
+        } }]))]
+    }));
+    assert!(book.hyphenate());
+    let spans = serde_json::to_value(&book.chapters[0].content).unwrap()[0]["Paragraph"]["content"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|span| {
+            span["Text"]["value"]
+                .as_str()
+                .unwrap()
+                .replace(SOFT_HYPHEN, "-")
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(spans, vec!["in-ter-na-tion-al-iza-tion ", "по-э-ма"]);
+}
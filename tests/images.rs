@@ -0,0 +1,97 @@
+#![cfg(feature = "images")]
+
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use image::{ImageFormat, Rgb, RgbImage};
+use json_book::{ImageProfile, VariantFormat};
+use serde_json::json;
+use uuid::Uuid;
+
+mod common;
+
+#[test]
+fn prepares_image_variants() {
+    let mut book = common::book(json!([]));
+    let mut png = Cursor::new(vec![]);
+    RgbImage::from_pixel(400, 200, Rgb([200, 30, 30]))
+        .write_to(&mut png, ImageFormat::Png)
+        .unwrap();
+    let scan = Uuid::from_u128(1);
+    let resources = HashMap::from([
+        (scan, png.into_inner()),
+        (Uuid::from_u128(2), b"not an image".to_vec()),
+    ]);
+    let profiles = [
+        ImageProfile {
+            max_width: 100,
+            max_height: 100,
+            format: VariantFormat::Jpeg { quality: 80 },
+            grayscale: false,
+        },
+        ImageProfile {
+            max_width: 1000,
+            max_height: 1000,
+            format: VariantFormat::WebP,
+            grayscale: true,
+        },
+    ];
+
+    let data = book.optimize_images(&resources, &profiles);
+
+    assert_eq!(book.resources.len(), 1);
+    let variants = &book.resources[&scan].variants;
+    let small = &variants[0];
+    assert_eq!((small.width, small.height), (100, 50));
+    assert_eq!(small.mime_type, "image/jpeg");
+    let decoded = image::load_from_memory(&data[&small.id]).unwrap();
+    assert_eq!((decoded.width(), decoded.height()), (100, 50));
+
+    // smaller images are not upscaled
+    let gray = &variants[1];
+    assert_eq!((gray.width, gray.height, gray.grayscale), (400, 200, true));
+    assert_eq!(gray.mime_type, "image/webp");
+    let decoded = image::load_from_memory(&data[&gray.id]).unwrap();
+    let Rgb([r, g, b]) = decoded.to_rgb8()[(0, 0)];
+    assert!(r == g && g == b);
+}
+
+#[test]
+fn fills_dominant_color_placeholders() {
+    let id = |n: u128| Uuid::from_u128(n);
+    let mut book = common::book_with(json!({
+        "cover": { "id": id(1) },
+        "chapters": [common::chapter(json!([
+            { "Image": { "id": id(1), "placeholder": { "BlurHash": "LEHV6nWB2yk8" } } },
+            { "Paragraph": { "content": [{ "Image": { "id": id(2) } }] } }
+        ]))]
+    }));
+    let mut png = Cursor::new(vec![]);
+    RgbImage::from_fn(40, 40, |_, y| {
+        if y < 10 {
+            Rgb([200, 30, 30])
+        } else {
+            Rgb([16, 32, 240])
+        }
+    })
+    .write_to(&mut png, ImageFormat::Png)
+    .unwrap();
+    let resources = HashMap::from([(id(1), png.into_inner()), (id(2), b"not an image".to_vec())]);
+
+    book.optimize_images(&resources, &[]);
+
+    let placeholders = serde_json::to_value(&book).unwrap();
+    assert_eq!(
+        placeholders["cover"]["placeholder"],
+        json!({ "Color": "#1020f0" })
+    );
+    let content = &placeholders["chapters"][0]["content"];
+    assert_eq!(
+        content[0]["Image"]["placeholder"],
+        json!({ "BlurHash": "LEHV6nWB2yk8" })
+    );
+    assert_eq!(
+        content[1]["Paragraph"]["content"][0]["Image"].get("placeholder"),
+        None
+    );
+}
@@ -0,0 +1,22 @@
+use json_book::Isbn;
+
+#[test]
+fn parses_and_validates_isbn() {
+    let isbn = Isbn::parse("ISBN-13: 978-0-306-40615-7").unwrap();
+    assert_eq!(isbn.as_str(), "9780306406157");
+    let isbn = Isbn::parse(" 0-8044-2957-x ").unwrap();
+    assert_eq!(isbn.as_str(), "080442957X");
+    assert_eq!(
+        Isbn::parse("0-306-40615-2").unwrap().to_isbn13(),
+        Isbn::parse("978-0-306-40615-7").unwrap()
+    );
+
+    // wrong check digits and lengths
+    assert_eq!(Isbn::parse("978-0-306-40615-8"), None);
+    assert_eq!(Isbn::parse("0-306-40615-3"), None);
+    assert_eq!(Isbn::parse("X-306-40615-2"), None);
+    assert_eq!(Isbn::parse("978030640615"), None);
+
+    assert!(serde_json::from_str::<Isbn>("\"9780306406157\"").is_ok());
+    assert!(serde_json::from_str::<Isbn>("\"9780306406158\"").is_err());
+}
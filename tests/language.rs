@@ -0,0 +1,58 @@
+use json_book::{Book, Direction, Part, Position};
+use serde_json::json;
+
+mod common;
+
+fn book() -> Book {
+    common::book_with(json!({
+        "language": "en",
+        "chapters": [{
+            "language": "de",
+            "content": [
+                { "Paragraph": { "content": [
+                    { "Text": { "value": "Er sagte " } },
+                    { "Text": { "value": "שלום", "language": "he" } }
+                ] } },
+                { "Cite": { "language": "fr", "direction": "Ltr", "content": [
+                    { "Paragraph": { "content": [{ "Text": { "value": "Bonjour" } }] } },
+                    { "Paragraph": { "language": "ar", "content": [{ "Text": { "value": "مرحبا" } }] } }
+                ] } }
+            ],
+            "sub_chapters": [{
+                "content": [{ "Paragraph": { "content": [{ "Text": { "value": "Hallo" } }] } }],
+                "sub_chapters": []
+            }]
+        }, {
+            "content": [{ "Paragraph": { "content": [{ "Text": { "value": "Hello" } }] } }],
+            "sub_chapters": []
+        }]
+    }))
+}
+
+#[test]
+fn resolves_inherited_languages() {
+    let book = book();
+    let language = |path: Vec<usize>, block: usize, offset: usize| {
+        book.effective_language(&Position::new(Part::Chapter(path), block, offset))
+            .map(|l| l.to_string())
+    };
+    assert_eq!(language(vec![0], 0, 0).as_deref(), Some("de"));
+    assert_eq!(language(vec![0], 0, 9).as_deref(), Some("he"));
+    assert_eq!(language(vec![0], 1, 0).as_deref(), Some("fr"));
+    assert_eq!(language(vec![0], 2, 0).as_deref(), Some("ar"));
+    assert_eq!(language(vec![0, 0], 0, 0).as_deref(), Some("de"));
+    assert_eq!(language(vec![1], 0, 0).as_deref(), Some("en"));
+    assert_eq!(language(vec![2], 0, 0), None);
+}
+
+#[test]
+fn resolves_directions() {
+    let book = book();
+    let direction = |block: usize, offset: usize| {
+        book.effective_direction(&Position::new(Part::Chapter(vec![0]), block, offset))
+    };
+    assert_eq!(direction(0, 0), Some(Direction::Ltr));
+    assert_eq!(direction(0, 10), Some(Direction::Rtl));
+    // the direction of the cite wins over the language of the paragraph
+    assert_eq!(direction(2, 0), Some(Direction::Ltr));
+}
@@ -0,0 +1,370 @@
+use json_book::{Layout, LayoutOptions, MonospaceMeasurer, Part, Position, RunContent, Viewport};
+use serde_json::json;
+
+mod common;
+
+use common::{book, paragraph};
+
+const MEASURER: MonospaceMeasurer = MonospaceMeasurer {
+    advance: 1.0,
+    line_height: 1.0,
+};
+
+fn options(width: f32, height: f32) -> LayoutOptions {
+    LayoutOptions::new(Viewport { width, height })
+}
+
+fn page_text(layout: &Layout, page: usize) -> Vec<(f32, String)> {
+    layout.pages()[page]
+        .runs
+        .iter()
+        .filter_map(|r| match &r.content {
+            RunContent::Text { text, .. } => Some((r.y, text.clone())),
+            RunContent::Image(_) => None,
+        })
+        .collect()
+}
+
+#[test]
+fn wraps_lines_at_spaces() {
+    let book = book(json!([{
+        "content": [paragraph("one two three four"), "EmptyLine", paragraph("five")],
+        "sub_chapters": []
+    }]));
+    let layout = Layout::new(&book, &MEASURER, &options(9.0, 10.0));
+    assert_eq!(layout.pages().len(), 1);
+    assert_eq!(
+        page_text(&layout, 0),
+        vec![
+            (0.0, "one two".to_string()),
+            (1.0, "three".to_string()),
+            (2.0, "four".to_string()),
+            (4.0, "five".to_string()),
+        ]
+    );
+    let page = &layout.pages()[0];
+    assert_eq!(page.start, Position::new(Part::Chapter(vec![0]), 0, 0));
+    assert_eq!(page.end, Position::new(Part::Chapter(vec![0]), 2, 4));
+    assert_eq!(
+        page.runs[1].position,
+        Position::new(Part::Chapter(vec![0]), 0, 8)
+    );
+}
+
+#[test]
+fn keeps_widows_and_orphans() {
+    let book = book(json!([{
+        "content": [paragraph("aa bb cc"), paragraph("dd ee ff gg")],
+        "sub_chapters": []
+    }]));
+    // 3 lines of the first paragraph leave a single line for the second one
+    let layout = Layout::new(&book, &MEASURER, &options(2.0, 4.0));
+    assert_eq!(layout.pages().len(), 2);
+    assert_eq!(page_text(&layout, 0).len(), 3);
+    assert_eq!(page_text(&layout, 1).len(), 4);
+
+    // 5 lines on a page would leave a single widow line
+    let book = self::book(json!([{
+        "content": [paragraph("aa bb cc dd ee ff")],
+        "sub_chapters": []
+    }]));
+    let layout = Layout::new(&book, &MEASURER, &options(2.0, 5.0));
+    assert_eq!(page_text(&layout, 0).len(), 4);
+    assert_eq!(page_text(&layout, 1).len(), 2);
+}
+
+#[test]
+fn maps_positions_to_pages() {
+    let book = book(json!([
+        {
+            "anchor": "first",
+            "content": [paragraph("aa bb"), paragraph("cc dd")],
+            "sub_chapters": []
+        },
+        {
+            "content": [{ "Poem": { "content": [{ "Stanza": { "content": [
+                { "content": [{ "Text": { "value": "verse one" } }] },
+                { "anchor": "last", "content": [{ "Text": { "value": "verse two" } }] }
+            ] } }] } }],
+            "sub_chapters": []
+        }
+    ]));
+    let layout = Layout::new(&book, &MEASURER, &options(20.0, 1.0));
+    assert_eq!(layout.pages().len(), 4);
+    let chapter = |c: usize, block: usize, offset: usize| {
+        Position::new(Part::Chapter(vec![c]), block, offset)
+    };
+    assert_eq!(layout.page_of(&chapter(0, 0, 0)), Some(0));
+    assert_eq!(layout.page_of(&chapter(0, 1, 4)), Some(1));
+    assert_eq!(layout.page_of(&chapter(1, 1, 3)), Some(3));
+    assert_eq!(layout.page_of_anchor("first"), Some(0));
+    assert_eq!(layout.page_of_anchor("last"), Some(3));
+    assert_eq!(layout.page_of(&Position::start_of(Part::Annotation)), None);
+    assert_eq!(layout.page_of(&chapter(5, 0, 0)), None);
+    assert_eq!(layout.page_of(&chapter(1, 2, 0)), None);
+    assert_eq!(layout.page_of(&Position::start_of(Part::Front)), None);
+}
+
+#[test]
+fn splits_titles_taller_than_the_page() {
+    let book = book(json!([{
+        "title": { "content": [paragraph("t1 t2 t3 t4 t5 t6 t7 t8 t9 t0")] },
+        "content": [paragraph("bb")],
+        "sub_chapters": []
+    }]));
+    let layout = Layout::new(&book, &MEASURER, &options(2.0, 5.0));
+    assert_eq!(layout.pages().len(), 3);
+    assert!(layout
+        .pages()
+        .iter()
+        .all(|p| p.runs.iter().all(|r| r.y + r.height <= 5.0)));
+    assert_eq!(page_text(&layout, 0).len(), 5);
+    assert_eq!(page_text(&layout, 2), vec![(0.0, "bb".to_string())]);
+}
+
+#[test]
+fn lays_out_table_rows() {
+    let cell = |text: &str| json!({ "content": [{ "Text": { "value": text } }] });
+    let book = book(json!([{
+        "content": [{ "Table": {
+            "header_column": false,
+            "header_row": false,
+            "rows": [
+                { "cells": [cell("a"), cell("b b")] },
+                { "cells": [cell("c"), cell("d")] }
+            ]
+        } }],
+        "sub_chapters": []
+    }]));
+    let layout = Layout::new(&book, &MEASURER, &options(6.0, 10.0));
+    let runs = &layout.pages()[0].runs;
+    let placed = runs.iter().map(|r| (r.x, r.y)).collect::<Vec<_>>();
+    assert_eq!(placed, vec![(0.0, 0.0), (3.0, 0.0), (0.0, 1.0), (3.0, 1.0)]);
+}
+
+#[test]
+fn spans_and_aligns_table_cells() {
+    let cell = |text: &str| json!({ "content": [{ "Text": { "value": text } }] });
+    let book = book(json!([{
+        "content": [{ "Table": {
+            "caption": { "content": [{ "Text": { "value": "T" } }] },
+            "header_column": false,
+            "header_row": true,
+            "rows": [
+                { "cells": [
+                    { "header": true, "row_span": 2, "content": [{ "Text": { "value": "a a" } }] },
+                    { "header": true, "column_span": 2, "horizontal_align": "Right", "vertical_align": "Bottom", "content": [{ "Text": { "value": "b" } }] }
+                ] },
+                { "cells": [cell("c"), cell("d")] }
+            ]
+        } }],
+        "sub_chapters": []
+    }]));
+    let layout = Layout::new(&book, &MEASURER, &options(6.0, 10.0));
+    let runs = &layout.pages()[0].runs;
+    let placed = runs
+        .iter()
+        .filter_map(|r| match &r.content {
+            RunContent::Text { text, style } => Some((r.x, r.y, text.as_str(), style.heading)),
+            RunContent::Image(_) => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        placed,
+        vec![
+            (2.5, 0.0, "T", false),
+            (0.0, 1.0, "a", true),
+            (0.0, 2.0, "a", true),
+            (5.0, 2.0, "b", true),
+            // the first column is taken by "a a"
+            (2.0, 3.0, "c", false),
+            (4.0, 3.0, "d", false),
+        ]
+    );
+}
+
+#[test]
+fn clamps_table_spans_to_the_table() {
+    let cell = |text: &str| json!({ "content": [{ "Text": { "value": text } }] });
+    let book = book(json!([{
+        "content": [{ "Table": {
+            "header_column": false,
+            "header_row": false,
+            "rows": [
+                { "cells": [
+                    { "column_span": u32::MAX, "row_span": u32::MAX, "content": [{ "Text": { "value": "a" } }] },
+                    cell("b")
+                ] },
+                { "cells": [cell("c"), cell("d")] }
+            ]
+        } }],
+        "sub_chapters": []
+    }]));
+    let layout = Layout::new(&book, &MEASURER, &options(6.0, 10.0));
+    let placed = layout.pages()[0]
+        .runs
+        .iter()
+        .map(|r| (r.x, r.y))
+        .collect::<Vec<_>>();
+    // "a" takes two columns and both rows, so "c" and "d" are pushed to the right
+    assert_eq!(placed, vec![(0.0, 0.0), (3.0, 0.0), (3.0, 1.0), (4.5, 1.0)]);
+}
+
+#[test]
+fn breaks_at_soft_hyphens() {
+    let book = book(json!([{
+        "content": [paragraph("an ex\u{AD}ten\u{AD}sive")],
+        "sub_chapters": []
+    }]));
+    let layout = Layout::new(&book, &MEASURER, &options(7.0, 10.0));
+    assert_eq!(
+        page_text(&layout, 0),
+        vec![(0.0, "an ex-".to_string()), (1.0, "tensive".to_string())]
+    );
+}
+
+#[test]
+fn marks_list_items() {
+    let item = |text: &str| json!({ "content": [paragraph(text)] });
+    let book = book(json!([{
+        "content": [{ "List": {
+            "kind": "Ordered",
+            "start": 9,
+            "items": [item("nine"), {
+                "content": [paragraph("ten"), { "List": { "kind": "Unordered", "items": [item("a")] } }]
+            }]
+        } }],
+        "sub_chapters": []
+    }]));
+    let mut options = options(20.0, 10.0);
+    options.nested_indent = 3.0;
+    let layout = Layout::new(&book, &MEASURER, &options);
+    let runs = layout.pages()[0]
+        .runs
+        .iter()
+        .filter_map(|r| match &r.content {
+            RunContent::Text { text, .. } => Some((r.x, r.y, text.as_str())),
+            RunContent::Image(_) => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        runs,
+        vec![
+            (0.0, 0.0, "9."),
+            (3.0, 0.0, "nine"),
+            (0.0, 1.0, "10."),
+            (3.0, 1.0, "ten"),
+            (3.0, 2.0, "•"),
+            (6.0, 2.0, "a"),
+        ]
+    );
+}
+
+#[test]
+fn keeps_whitespace_of_preformatted_lines() {
+    let book = book(json!([{
+        "content": [{ "Preformatted": { "language": "rust", "lines": ["fn main() {", "    run();", "", "}"] } }],
+        "sub_chapters": []
+    }]));
+    let mut options = options(20.0, 10.0);
+    options.paragraph_spacing = 1.0;
+    let layout = Layout::new(&book, &MEASURER, &options);
+    assert_eq!(
+        page_text(&layout, 0),
+        vec![
+            (0.0, "fn main() {".to_string()),
+            (1.0, "    run();".to_string()),
+            (3.0, "}".to_string()),
+        ]
+    );
+    assert!(layout.pages()[0].runs.iter().all(|r| matches!(
+        r.content,
+        RunContent::Text { style, .. } if style.monospace
+    )));
+}
+
+#[test]
+fn centers_scene_breaks() {
+    let book = book(json!([{
+        "content": [paragraph("one"), "SceneBreak", paragraph("two")],
+        "sub_chapters": []
+    }]));
+    let layout = Layout::new(&book, &MEASURER, &options(9.0, 10.0));
+    let run = &layout.pages()[0].runs[1];
+    assert_eq!((run.x, run.y, run.width), (2.0, 1.0, 5.0));
+    assert_eq!(page_text(&layout, 0)[1].1, "* * *");
+    assert_eq!(
+        layout.pages()[0].end,
+        Position::new(Part::Chapter(vec![0]), 2, 3)
+    );
+}
+
+#[test]
+fn sets_formulas_as_alt_text() {
+    let book = book(json!([{
+        "content": [
+            { "Paragraph": { "content": [
+                { "Text": { "value": "so " } },
+                { "Math": { "tex": "x^2", "alt": "x²" } }
+            ] } },
+            { "Math": { "tex": "\\sqrt{2}", "alt": "√2" } }
+        ],
+        "sub_chapters": []
+    }]));
+    let layout = Layout::new(&book, &MEASURER, &options(10.0, 10.0));
+    let runs = &layout.pages()[0].runs;
+    assert_eq!(page_text(&layout, 0)[1], (0.0, "x²".to_string()));
+    assert!(matches!(runs[1].content, RunContent::Text { style, .. } if style.italic));
+    assert_eq!((runs[2].x, runs[2].y), (4.0, 1.0));
+    assert_eq!(
+        layout.pages()[0].end,
+        Position::new(Part::Chapter(vec![0]), 1, 2)
+    );
+}
+
+#[test]
+fn sets_ruby_after_the_base() {
+    let book = book(json!([{
+        "content": [{ "Paragraph": { "content": [
+            { "Ruby": { "base": [{ "value": "漢字" }], "annotation": "かんじ" } },
+            { "Text": { "value": " is" } }
+        ] } }],
+        "sub_chapters": []
+    }]));
+    let layout = Layout::new(&book, &MEASURER, &options(20.0, 10.0));
+    assert_eq!(
+        page_text(&layout, 0),
+        vec![(0.0, "漢字(かんじ) is".to_string())]
+    );
+    let start = Position::new(Part::Chapter(vec![0]), 0, 0);
+    let end = Position::new(Part::Chapter(vec![0]), 0, 10);
+    assert_eq!(
+        book.text_between(&start, &end).as_deref(),
+        Some("漢字(かんじ) is")
+    );
+}
+
+#[test]
+fn keeps_image_aspect_ratio() {
+    let book = book(json!([{
+        "content": [
+            { "Image": {
+                "id": "00000000-0000-0000-0000-000000000001",
+                "width": 400,
+                "height": 200,
+                "caption": [{ "content": [{ "Text": { "value": "Map" } }] }]
+            } },
+            paragraph("after")
+        ],
+        "sub_chapters": []
+    }]));
+    // the box is 10 wide and 5 high, so the image is scaled to fill its width
+    let layout = Layout::new(&book, &MEASURER, &options(10.0, 10.0));
+    let image = &layout.pages()[0].runs[0];
+    assert!(matches!(image.content, RunContent::Image(_)));
+    assert_eq!((image.x, image.width, image.height), (0.0, 10.0, 5.0));
+    assert_eq!(
+        page_text(&layout, 0),
+        vec![(5.0, "Map".to_string()), (6.0, "after".to_string())]
+    );
+}
@@ -0,0 +1,70 @@
+use json_book::{Content, List};
+use serde_json::json;
+
+mod common;
+
+use common::paragraph;
+
+#[test]
+fn lowers_lists_to_paragraphs() {
+    let list: List = serde_json::from_value(json!({
+        "anchor": "list",
+        "kind": "Ordered",
+        "start": 3,
+        "items": [
+            { "content": [paragraph("Three"), paragraph("More of three")] },
+            { "content": [{ "List": { "kind": "Unordered", "items": [{ "content": [paragraph("Nested")] }] } }] }
+        ]
+    }))
+    .unwrap();
+    let content = list.into_paragraphs();
+    let texts = content
+        .iter()
+        .map(|c| match c {
+            Content::Paragraph(p) => serde_json::to_value(&p.content).unwrap(),
+            _ => panic!("not a paragraph: {c:?}"),
+        })
+        .collect::<Vec<_>>();
+    let spans = |texts: &[&str]| {
+        json!(texts
+            .iter()
+            .map(|t| json!({ "Text": { "value": t } }))
+            .collect::<Vec<_>>())
+    };
+    assert_eq!(
+        texts,
+        vec![
+            spans(&["3. ", "Three"]),
+            spans(&["\u{a0}\u{a0}\u{a0}\u{a0}", "More of three"]),
+            spans(&["4."]),
+            spans(&["\u{a0}\u{a0}\u{a0}\u{a0}• ", "Nested"]),
+        ]
+    );
+    assert!(matches!(&content[0], Content::Paragraph(p) if p.anchor.as_deref() == Some("list")));
+}
+
+#[test]
+fn numbers_stop_at_the_maximum() {
+    let list = json!({
+        "kind": "Ordered",
+        "start": u32::MAX,
+        "items": [
+            { "content": [paragraph("One")] },
+            { "content": [paragraph("Two")] }
+        ]
+    });
+    let book = common::book(json!([common::chapter(json!([{ "List": list.clone() }]))]));
+    assert_eq!(book.stats().total.paragraphs, 2);
+
+    let list: List = serde_json::from_value(list).unwrap();
+    let numbers = list
+        .into_paragraphs()
+        .iter()
+        .map(|c| match c {
+            Content::Paragraph(p) => serde_json::to_value(&p.content[0]).unwrap(),
+            _ => panic!("not a paragraph: {c:?}"),
+        })
+        .collect::<Vec<_>>();
+    let number = json!({ "Text": { "value": "4294967295. " } });
+    assert_eq!(numbers, vec![number.clone(), number]);
+}
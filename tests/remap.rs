@@ -0,0 +1,73 @@
+use json_book::{remap_positions, Part, Position};
+use serde_json::json;
+
+mod common;
+
+use common::{book, paragraph};
+
+fn chapter(c: usize, block: usize, offset: usize) -> Position {
+    Position::new(Part::Chapter(vec![c]), block, offset)
+}
+
+#[test]
+fn remaps_positions_to_a_new_revision() {
+    let old = book(json!([
+        {
+            "content": [
+                paragraph("Unchanged opening paragraph."),
+                paragraph("It was a bright cold day in April, and the clocks were striking thirteen."),
+                { "Paragraph": { "anchor": "p3", "content": [{ "Text": { "value": "Winston Smith slipped quickly through the glass doors." } }] } }
+            ],
+            "sub_chapters": []
+        },
+        { "content": [paragraph("Nothing like this exists anymore")], "sub_chapters": [] }
+    ]));
+    let new = book(json!([
+        { "content": [paragraph("A new foreword.")], "sub_chapters": [] },
+        {
+            "content": [
+                paragraph("Unchanged opening paragraph."),
+                paragraph("It was a bright, cold day in April, and the clocks were striking thirteen."),
+                { "Paragraph": { "anchor": "p3", "content": [{ "Text": { "value": "Winston Smith slipped fast through the doors." } }] } }
+            ],
+            "sub_chapters": []
+        }
+    ]));
+    let remapped = remap_positions(
+        &old,
+        &new,
+        &[
+            chapter(0, 0, 9),
+            chapter(0, 1, 42),
+            chapter(0, 2, 22),
+            chapter(1, 0, 0),
+            chapter(5, 0, 0),
+        ],
+    );
+
+    let first = remapped[0].as_ref().unwrap();
+    assert_eq!(first.position, chapter(1, 0, 9));
+    assert!(first.confidence > 0.85);
+
+    // "the clocks" moved one character further
+    let second = remapped[1].as_ref().unwrap();
+    assert_eq!(second.position, chapter(1, 1, 43));
+    assert!(second.confidence > 0.6 && second.confidence < first.confidence);
+
+    // "quickly" became "fast", the block is found by its anchor
+    let third = remapped[2].as_ref().unwrap();
+    assert_eq!(third.position, chapter(1, 2, 22));
+    assert!(third.confidence < 0.6);
+
+    assert_eq!(remapped[3], None);
+    assert_eq!(remapped[4], None);
+}
+
+#[test]
+fn keeps_positions_in_unchanged_blocks() {
+    let book = || book(json!([{ "content": [paragraph("Same")], "sub_chapters": [] }]));
+    let remapped = remap_positions(&book(), &book(), &[chapter(0, 0, 2)]);
+    let position = remapped[0].as_ref().unwrap();
+    assert_eq!(position.position, chapter(0, 0, 2));
+    assert_eq!(position.confidence, 1.0);
+}
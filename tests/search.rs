@@ -0,0 +1,74 @@
+use json_book::{Book, Part, Position, SearchIndex};
+use serde_json::json;
+
+mod common;
+
+fn book() -> Book {
+    common::book_with(json!({
+        "chapters": [{
+            "title": { "content": [{ "Paragraph": { "content": [{ "Text": { "value": "Ёлка" } }] } }] },
+            "content": [
+                { "Paragraph": { "content": [
+                    { "Text": { "value": "Под елкой лежал " } },
+                    { "Text": { "value": "Café", "font_weight": 600 } },
+                    { "Text": { "value": "-прей\u{AD}скурант" } },
+                    { "Footnote": { "id": "n1", "type": "Note", "content": [{ "value": "[1]" }] } }
+                ] } },
+                { "Table": {
+                    "header_column": false,
+                    "header_row": false,
+                    "rows": [{ "cells": [{ "content": [{ "Text": { "value": "мой кафе" } }] }] }]
+                } }
+            ],
+            "sub_chapters": []
+        }],
+        "notes": { "content": { "n1": { "content": [
+            { "Paragraph": { "content": [{ "Text": { "value": "Мои заметки о ёлках" } }] } }
+        ] } } }
+    }))
+}
+
+#[test]
+fn finds_folded_phrases_across_spans() {
+    let index = SearchIndex::new(&book());
+    let chapter =
+        |block: usize, offset: usize| Position::new(Part::Chapter(vec![0]), block, offset);
+
+    let hits = index.search("ЁЛКА");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].start, chapter(0, 0));
+
+    let hits = index.search("лежал cafe прейскурант");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].start, chapter(1, 10));
+    assert_eq!(hits[0].end, chapter(1, 33));
+    assert_eq!(hits[0].before, "Под елкой ");
+    assert_eq!(hits[0].matched, "лежал Café-прей\u{AD}скурант");
+    assert_eq!(hits[0].after, "[1]");
+
+    let hits = index.search("кафе");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].start, chapter(2, 4));
+
+    // "й" is not folded into "и"
+    let hits = index.search("мой");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].start.part, Part::Chapter(vec![0]));
+    let hits = index.search("мои заметки");
+    assert_eq!(hits.len(), 1);
+    assert_eq!(
+        hits[0].start,
+        Position::new(Part::Note("n1".to_string()), 0, 0)
+    );
+
+    assert!(index.search("елкой лежит").is_empty());
+    assert!(index.search(" ,").is_empty());
+}
+
+#[test]
+fn serializes_index() {
+    let index = SearchIndex::new(&book());
+    let json = serde_json::to_string(&index).unwrap();
+    let index: SearchIndex = serde_json::from_str(&json).unwrap();
+    assert_eq!(index.search("ёлках").len(), 1);
+}
@@ -0,0 +1,48 @@
+use json_book::{sort_by_series, Book};
+use serde_json::{json, Value};
+
+mod common;
+
+fn book(title: &str, sequences: Value) -> Book {
+    common::book_with(json!({ "short_title": title, "sequences": sequences }))
+}
+
+#[test]
+fn sorts_books_by_series_order() {
+    let mut books = vec![
+        book("Standalone", json!([])),
+        book("Extras", json!([{ "name": "Discworld" }])),
+        book(
+            "Mort",
+            json!([{ "name": "Discworld", "number": 4, "sequences": [{ "name": "Death", "number": 1 }] }]),
+        ),
+        book(
+            "Reaper Man",
+            json!([
+                { "name": "Paperbacks", "number": 1, "publisher": true },
+                { "name": "Discworld", "number": 11 }
+            ]),
+        ),
+        book(
+            "The Colour of Magic",
+            json!([{ "name": "discworld", "number": 1 }]),
+        ),
+        book(
+            "Anthology",
+            json!([{ "name": "Paperbacks", "number": 2, "publisher": true }]),
+        ),
+    ];
+    sort_by_series(&mut books);
+    let titles: Vec<_> = books.iter().map(|b| b.short_title.as_str()).collect();
+    assert_eq!(
+        titles,
+        vec![
+            "The Colour of Magic",
+            "Mort",
+            "Reaper Man",
+            "Extras",
+            "Anthology",
+            "Standalone"
+        ]
+    );
+}
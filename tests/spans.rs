@@ -0,0 +1,72 @@
+use json_book::{Book, Content, SpanReport};
+use serde_json::{json, Value};
+
+mod common;
+
+fn book(content: Value) -> Book {
+    common::book(common::single_paragraph(content))
+}
+
+fn spans(book: &Book) -> Value {
+    let Content::Paragraph(paragraph) = &book.chapters[0].content[0] else {
+        panic!("not a paragraph");
+    };
+    serde_json::to_value(&paragraph.content).unwrap()
+}
+
+#[test]
+fn merges_and_trims_texts() {
+    let mut book = book(json!([
+        { "Text": { "value": "\n  One " } },
+        { "Text": { "value": " two", "font_weight": 600 } },
+        { "Text": { "value": " " } },
+        { "Text": { "value": "three", "font_weight": 600 } },
+        { "Text": { "value": "" } },
+        { "Link": { "href": { "Local": "a" }, "content": [
+            { "value": " four" },
+            { "value": "" },
+            { "value": "  five" }
+        ] } },
+        { "Text": { "value": "  x  y ", "font_style": ["Code"] } },
+        { "Text": { "value": " six \n" } }
+    ]));
+    let report = book.normalize_spans();
+    assert_eq!(
+        spans(&book),
+        json!([
+            { "Text": { "value": "One " } },
+            { "Text": { "value": "two three", "font_weight": 600 } },
+            { "Link": { "href": { "Local": "a" }, "content": [{ "value": " four five" }] } },
+            { "Text": { "value": "  x  y ", "font_style": ["Code"] } },
+            { "Text": { "value": " six" } }
+        ])
+    );
+    assert_eq!(
+        report,
+        SpanReport {
+            spans_before: 8,
+            spans_after: 5,
+            text_bytes_before: 41,
+            text_bytes_after: 34,
+        }
+    );
+}
+
+#[test]
+fn keeps_footnotes_and_links_without_text() {
+    let mut book = book(json!([
+        { "Text": { "value": "Word " } },
+        { "Footnote": { "id": "n1", "type": "Note", "content": [{ "value": " " }] } },
+        { "Link": { "href": { "Local": "a" }, "content": [] } },
+        { "Text": { "value": " " } }
+    ]));
+    book.normalize_spans();
+    assert_eq!(
+        spans(&book),
+        json!([
+            { "Text": { "value": "Word" } },
+            { "Footnote": { "id": "n1", "type": "Note", "content": [] } },
+            { "Link": { "href": { "Local": "a" }, "content": [] } }
+        ])
+    );
+}
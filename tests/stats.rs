@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use json_book::{Book, Part, ReadingSpeed, Stats};
+use serde_json::json;
+
+mod common;
+
+use common::paragraph;
+
+fn book() -> Book {
+    common::book_with(json!({
+        "language": "ja",
+        "chapters": [{
+            "title": { "content": [paragraph("First")] },
+            "content": [
+                { "Paragraph": { "content": [
+                    { "Text": { "value": "吾輩は猫である。 Soseki wrote it" } },
+                    { "Footnote": { "id": "n1", "type": "Note", "content": [{ "value": "[1]" }] } }
+                ] } },
+                { "Poem": { "content": [
+                    { "Stanza": { "content": [{ "content": [{ "Text": { "value": "one" } }] }] } },
+                    { "Stanza": { "content": [{ "content": [{ "Text": { "value": "two" } }] }] } }
+                ] } },
+                { "Image": { "id": "00000000-0000-0000-0000-000000000001" } }
+            ],
+            "sub_chapters": [{
+                "content": [
+                    paragraph("Deep down"),
+                    { "Table": {
+                        "header_column": false,
+                        "header_row": false,
+                        "rows": [{ "cells": [
+                            { "content": [{ "Text": { "value": "a" } }] },
+                            { "content": [{ "Text": { "value": "b" } }] }
+                        ] }]
+                    } }
+                ],
+                "sub_chapters": []
+            }]
+        }],
+        "notes": { "content": { "n1": { "content": [paragraph("A note")] } } }
+    }))
+}
+
+#[test]
+fn counts_chapters_and_total() {
+    let stats = book().stats();
+    assert_eq!(stats.chapters.len(), 2);
+    assert_eq!(stats.chapters[0].part, Part::Chapter(vec![0]));
+    assert_eq!(stats.chapters[0].title.as_deref(), Some("First"));
+    assert_eq!(
+        stats.chapters[0].stats,
+        Stats {
+            // 7 kana and ideographs, "First", "Soseki", "wrote", "it[1]", "one", "two"
+            words: 13,
+            characters: 5 + 24 + 3 + 3 + 3,
+            paragraphs: 1,
+            images: 1,
+            footnotes: 1,
+            tables: 0,
+            poems: 1,
+        }
+    );
+    assert_eq!(stats.chapters[1].title, None);
+    assert_eq!(stats.chapters[1].stats.tables, 1);
+    assert_eq!(stats.chapters[1].stats.words, 4);
+    assert_eq!(stats.total.words, 13 + 4 + 2);
+    assert_eq!(stats.total.paragraphs, 3);
+}
+
+#[test]
+fn estimates_reading_time() {
+    let book = book();
+    let speed = book.reading_speed();
+    assert_eq!(speed.words_per_minute, 357.0);
+    let stats = Stats {
+        words: 714,
+        ..Stats::default()
+    };
+    assert_eq!(stats.reading_time(&speed), Some(Duration::from_secs(120)));
+    assert_eq!(
+        stats.reading_time(&ReadingSpeed {
+            words_per_minute: 0.0
+        }),
+        None
+    );
+    assert_eq!(
+        ReadingSpeed::for_language(None).words_per_minute,
+        ReadingSpeed::for_language(Some(&"ru".parse().unwrap())).words_per_minute
+    );
+}
+
+#[test]
+fn estimates_words_of_unspaced_scripts() {
+    let book = common::book(json!([common::chapter(json!([paragraph(
+        "สวัสดีครับ ผมชื่อสมชาย Thai"
+    )]))]));
+    assert_eq!(book.stats().total.words, 6);
+}
@@ -0,0 +1,72 @@
+use json_book::{Book, Part, Position, TocOptions};
+use serde_json::json;
+
+mod common;
+
+use common::paragraph;
+
+fn book() -> Book {
+    common::book(json!([
+            {
+                "anchor": "part1",
+                "title": { "content": [paragraph("Part one"), "EmptyLine", paragraph(" The  beginning ")] },
+                "content": [paragraph("Three words here")],
+                "sub_chapters": [
+                    {
+                        "title": { "content": [paragraph("Chapter 1:"), paragraph("Arrival")] },
+                        "content": [paragraph("One two")],
+                        "sub_chapters": [
+                            {
+                                "content": [paragraph("Deep")],
+                                "sub_chapters": []
+                            }
+                        ]
+                    },
+                    {
+                        "content": [paragraph("Four — five six seven")],
+                        "sub_chapters": []
+                    }
+                ]
+            }
+    ]))
+}
+
+#[test]
+fn toc_tree() {
+    let toc = book().toc(&TocOptions {
+        untitled: "Chapter {n}".to_string(),
+        max_depth: None,
+    });
+    assert_eq!(toc.len(), 1);
+    let part = &toc[0];
+    assert_eq!(part.title, "Part one. The beginning");
+    assert_eq!(part.anchor.as_deref(), Some("part1"));
+    assert_eq!(part.word_count, 17);
+    assert_eq!(part.children.len(), 2);
+
+    let first = &part.children[0];
+    assert_eq!(first.depth, 1);
+    assert_eq!(first.title, "Chapter 1: Arrival");
+    assert_eq!(first.word_count, 6);
+    assert_eq!(first.children[0].title, "Chapter 1");
+    assert_eq!(
+        first.children[0].position,
+        Position::start_of(Part::Chapter(vec![0, 0, 0]))
+    );
+
+    let second = &part.children[1];
+    assert_eq!(second.title, "Chapter 2");
+    assert_eq!(second.word_count, 4);
+}
+
+#[test]
+fn toc_max_depth() {
+    let toc = book().toc(&TocOptions {
+        max_depth: Some(1),
+        ..TocOptions::default()
+    });
+    let first = &toc[0].children[0];
+    assert!(first.children.is_empty());
+    assert_eq!(first.word_count, 6);
+    assert_eq!(toc[0].children[1].title, "2");
+}
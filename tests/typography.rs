@@ -0,0 +1,106 @@
+use json_book::{Book, Content, TypographyOptions};
+use serde_json::{json, Value};
+
+mod common;
+
+fn book(language: &str, content: Value) -> Book {
+    common::book_with(json!({
+        "language": language,
+        "chapters": common::single_paragraph(content)
+    }))
+}
+
+fn texts(book: &Book) -> Vec<String> {
+    let Content::Paragraph(paragraph) = &book.chapters[0].content[0] else {
+        panic!("not a paragraph");
+    };
+    serde_json::to_value(&paragraph.content)
+        .unwrap()
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|span| span["Text"]["value"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn normalizes_russian_text() {
+    let mut book = book(
+        "ru",
+        json!([
+            { "Text": { "value": "- Он сказал: \"Это " } },
+            { "Text": { "value": "\"пример\"", "font_style": ["Italic"] } },
+            { "Text": { "value": "\"  - и  ушел в 1941-1945... " } },
+            { "Text": { "value": "a  \"b\" -- c", "font_style": ["Code"] } }
+        ]),
+    );
+    book.normalize_typography(&TypographyOptions::default());
+    assert_eq!(
+        texts(&book),
+        vec![
+            "—\u{A0}Он сказал: «Это ",
+            "„пример“",
+            "»\u{A0}— и\u{A0}ушел в\u{A0}1941–1945… ",
+            "a  \"b\" -- c",
+        ]
+    );
+}
+
+#[test]
+fn respects_options_and_language() {
+    let mut book = book(
+        "en",
+        json!([{ "Text": { "value": "A \"quoted\"  word -- and..." } }]),
+    );
+    book.normalize_typography(&TypographyOptions {
+        non_breaking_spaces: false,
+        ellipses: false,
+        ..TypographyOptions::default()
+    });
+    assert_eq!(texts(&book), vec!["A “quoted” word — and..."]);
+}
+
+#[test]
+fn keeps_dates_phone_numbers_and_urls() {
+    let mut book = book(
+        "en",
+        json!([
+            { "Text": { "value": "Pages 5-7, 2024-01-15, call 555-1234 or see www.example.com/a--b and " } },
+            { "Link": { "href": { "Remote": "https://example.com/" }, "content": [{ "value": "\"a--b\" 1-2" }] } }
+        ]),
+    );
+    book.normalize_typography(&TypographyOptions::default());
+    let Content::Paragraph(paragraph) = &book.chapters[0].content[0] else {
+        panic!("not a paragraph");
+    };
+    assert_eq!(
+        serde_json::to_value(&paragraph.content).unwrap(),
+        json!([
+            { "Text": { "value": "Pages 5–7, 2024-01-15, call 555-1234 or see www.example.com/a--b and " } },
+            { "Link": { "href": { "Remote": "https://example.com/" }, "content": [{ "value": "\"a--b\" 1-2" }] } }
+        ])
+    );
+}
+
+#[test]
+fn quotes_by_language_of_paragraph() {
+    let mut book = common::book_with(json!({
+        "language": "ru",
+        "chapters": [common::chapter(json!([
+            { "Paragraph": { "content": [{ "Text": { "value": "\"Да\"" } }] } },
+            { "Paragraph": { "language": "en", "content": [{ "Text": { "value": "\"Yes\"" } }] } }
+        ]))]
+    }));
+    book.normalize_typography(&TypographyOptions::default());
+    let texts = book.chapters[0]
+        .content
+        .iter()
+        .map(|c| match c {
+            Content::Paragraph(p) => {
+                serde_json::to_value(&p.content).unwrap()[0]["Text"]["value"].clone()
+            }
+            _ => panic!("not a paragraph: {c:?}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(texts, vec!["«Да»", "“Yes”"]);
+}
@@ -1,4 +1,10 @@
 use boon::{Compiler, Schemas};
+use json_book::Book;
+use serde_json::json;
+
+mod common;
+
+use common::paragraph;
 
 #[test]
 fn validate_schema() {
@@ -7,3 +13,87 @@ fn validate_schema() {
     let valid = compiler.compile("schema.json", &mut schemas).is_ok();
     assert!(valid);
 }
+
+#[test]
+fn validate_content_elements() {
+    let book = json!({
+        "id": "00000000-0000-0000-0000-000000000000",
+        "short_title": "Book",
+        "date": {},
+        "authors": [],
+        "chapters": [{
+            "content": [
+                { "List": { "classes": ["steps"], "kind": "Ordered", "start": 2, "items": [
+                    { "content": [paragraph("Two"), { "List": { "kind": "Unordered", "items": [{ "content": [paragraph("Nested")] }] } }] }
+                ] } },
+                { "Preformatted": { "classes": ["listing"], "language": "rust", "lines": ["fn main() {}"] } },
+                "SceneBreak",
+                { "Table": {
+                    "caption": { "content": [{ "Text": { "value": "Totals" } }] },
+                    "header_column": false,
+                    "header_row": true,
+                    "rows": [{ "cells": [{
+                        "classes": ["total"],
+                        "header": true,
+                        "column_span": 2,
+                        "row_span": 1,
+                        "horizontal_align": "Center",
+                        "vertical_align": "Middle",
+                        "content": [{ "Text": { "value": "Year" } }]
+                    }] }]
+                } },
+                { "Image": {
+                    "id": "00000000-0000-0000-0000-000000000001",
+                    "classes": ["map"],
+                    "width": 640,
+                    "height": 480,
+                    "mime_type": "image/png",
+                    "placeholder": { "Color": "#336699" },
+                    "caption": [{ "content": [{ "Text": { "value": "Map" } }] }]
+                } },
+                { "Math": { "classes": ["euler"], "tex": "e^{i\\pi} + 1 = 0", "alt": "e to the i pi plus one equals zero" } },
+                { "Paragraph": { "classes": ["sign"], "content": [
+                    { "Ruby": { "base": [{ "value": "漢字" }], "annotation": "かんじ" } },
+                    { "Text": { "value": "Energy is ", "classes": ["letter"] } },
+                    { "Image": { "id": "00000000-0000-0000-0000-000000000002", "width": 3, "height": 2, "placeholder": { "BlurHash": "LEHV6nWB2yk8pyo0adR*.7kCMdnj" } } },
+                    { "Text": { "value": "Пушкин", "font_style": ["SmallCaps"], "decorations": ["Underline"] } },
+                    { "Text": { "value": "т. е.", "decorations": ["Highlight"], "expansion": "то есть" } },
+                    { "Math": { "mathml": "<math><mi>E</mi><mo>=</mo><mi>m</mi><msup><mi>c</mi><mn>2</mn></msup></math>", "alt": "E = mc²" } }
+                ] } }
+            ],
+            "sub_chapters": []
+        }],
+        "contributors": [{
+            "person": { "id": "00000000-0000-0000-0000-000000000000", "full_name": "Нора Галь" },
+            "roles": ["trl", "edt"]
+        }],
+        "identifiers": [
+            { "Isbn": "9780306406157" },
+            { "Other": { "scheme": "fb2", "value": "6F1C1B2A-notes" } }
+        ],
+        "publication": { "publisher": "Analytical Press", "city": "London", "date": { "display_date": "1843" } },
+        "rights": { "copyright_holders": ["Analytical Press"], "license": "CC-BY-4.0" },
+        "sequences": [{
+            "name": "Собрание сочинений",
+            "number": 8,
+            "language": "ru",
+            "sequences": [{ "name": "Романы", "number": 3 }]
+        }, { "name": "Библиотека всемирной литературы", "publisher": true }],
+        "resources": {
+            "00000000-0000-0000-0000-000000000001": { "variants": [{
+                "id": "00000000-0000-0000-0000-000000000003",
+                "mime_type": "image/jpeg",
+                "width": 320,
+                "height": 240,
+                "grayscale": true
+            }] }
+        }
+    });
+    let parsed: Book = serde_json::from_value(book.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&parsed).unwrap(), book);
+
+    let mut schemas = Schemas::new();
+    let mut compiler = Compiler::new();
+    let index = compiler.compile("schema.json", &mut schemas).unwrap();
+    assert!(schemas.validate(&book, index).is_ok());
+}